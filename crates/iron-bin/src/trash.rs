@@ -14,20 +14,36 @@
 
 //! Trash.
 
+mod check;
 mod dir_sizes;
 mod info;
+mod query;
 
-use std::{cell::OnceCell, fs, io, os::unix::fs::MetadataExt};
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::{BTreeMap, HashSet},
+    fmt::{self, Display},
+    fs,
+    io::{self, Write},
+    os::unix::fs::MetadataExt,
+};
 
 use anyhow::{Context, Result, bail};
-use camino::{Utf8Path, Utf8PathBuf};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 use camino_ext::Utf8PathExt;
 use chrono::{Local, NaiveDateTime};
 
-use self::dir_sizes::DirSizes;
+use self::dir_sizes::{DirSize, DirSizes};
 use self::info::TrashInfo;
 
+pub use self::check::TrashProblem;
+pub use self::query::TrashQuery;
+
+const DIR_INFO: &str = "info";
+const DIR_FILES: &str = "files";
 const EXT_TRASHINFO: &str = "trashinfo";
+const DIRECTORYSIZES_TEMP_PREFIX: &str = "directorysizes.";
+const DIRECTORYSIZES_TEMP_SUFFIX: &str = ".tmp";
 
 /// Trash.
 #[derive(Clone, Debug, PartialEq)]
@@ -37,31 +53,75 @@ pub struct Trash {
     files_dir: Utf8PathBuf,
     directorysizes_file: Utf8PathBuf,
     dir_sizes: OnceCell<DirSizes>,
+    dry_run: bool,
+    /// Identifiers handed out by [`put`](Self::put) in dry-run mode.
+    dry_run_identifiers: RefCell<HashSet<String>>,
+    operation: Option<String>,
 }
 
 /// Trash entry.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TrashEntry {
     identifier: String,
     original_path: Utf8PathBuf,
     deletion_time: NaiveDateTime,
     size: u64,
+    file_type: TrashFileType,
+    trash_dir: Utf8PathBuf,
+    operation: Option<String>,
+}
+
+/// Error on a broken trash entry.
+#[derive(Debug)]
+pub struct TrashEntryError {
+    identifier: String,
+    trashinfo_path: Utf8PathBuf,
+    source: anyhow::Error,
+}
+
+/// Type of the file of a trash entry.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum TrashFileType {
+    /// Regular file.
+    File,
+
+    /// Directory.
+    Dir,
+
+    /// Symbolic link.
+    Symlink,
 }
 
 /// Report of the [`Trash::put`] operation.
 #[derive(Clone, Debug, PartialEq)]
 pub struct TrashPutReport {
+    pub identifier: String,
     pub path: Utf8PathBuf,
     pub deletion_time: NaiveDateTime,
+    /// Size of the path on disk, only computed in dry-run mode.
+    pub size: Option<u64>,
 }
 
 /// Report of the [`Trash::restore`] operation.
 #[derive(Clone, Debug, PartialEq)]
 pub struct TrashRestoreReport {
+    pub identifier: String,
     pub path: Utf8PathBuf,
     pub deletion_time: NaiveDateTime,
 }
 
+/// Report of the [`Trash::remove`] operation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrashRemoveReport {
+    pub identifier: String,
+    pub path: Utf8PathBuf,
+    pub deletion_time: NaiveDateTime,
+    pub size: u64,
+}
+
 /// Report of the [`Trash::empty`] operation.
 #[derive(Clone, Debug, PartialEq)]
 pub struct TrashEmptyReport {
@@ -93,8 +153,8 @@ impl Trash {
     /// Create a trash at the given base directory.
     pub fn new(base_dir: impl Into<Utf8PathBuf>) -> Self {
         let base_dir = base_dir.into();
-        let info_dir = base_dir.join("info");
-        let files_dir = base_dir.join("files");
+        let info_dir = base_dir.join(DIR_INFO);
+        let files_dir = base_dir.join(DIR_FILES);
         let directorysizes_file = base_dir.join("directorysizes");
         Self {
             base_dir,
@@ -102,20 +162,165 @@ impl Trash {
             files_dir,
             directorysizes_file,
             dir_sizes: OnceCell::new(),
+            dry_run: false,
+            dry_run_identifiers: RefCell::default(),
+            operation: None,
         }
     }
 
+    /// Return a new operation identifier.
+    ///
+    /// The identifier is based on the current time and process,
+    /// so that it is unique in practice.
+    pub fn new_operation() -> String {
+        let now = Local::now().naive_local();
+        format!("{}-{}", now.format("%Y%m%dT%H%M%S%.6f"), std::process::id())
+    }
+
+    /// Return this trash in dry-run mode or not.
+    ///
+    /// In dry-run mode, the operations that would change the file system
+    /// ([`put`](Self::put), [`restore`](Self::restore), [`remove`](Self::remove), [`empty`](Self::empty)
+    /// and [`repair`](Self::repair))
+    /// are planned and checked as far as possible, but not performed.
+    /// Their reports are the same as if they had been performed.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Return whether this trash is in dry-run mode.
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Return this trash recording the given operation or none.
+    ///
+    /// The operation is recorded in the `.trashinfo` file of each path [put](Self::put) in this trash,
+    /// so that all the paths put by the same operation can be found later, e.g. to undo the operation.
+    /// See [`new_operation`](Self::new_operation).
+    pub fn with_operation(mut self, operation: Option<String>) -> Self {
+        self.operation = operation;
+        self
+    }
+
+    /// Return the most recent operation recorded in this trash, if any.
+    ///
+    /// Entries in error are ignored.
+    pub fn last_operation(&self) -> Result<Option<String>> {
+        // NOTE: Deletion times have a resolution of one second,
+        // so ties are broken with the operations, which start with a more precise time
+        let operation = self
+            .entries()?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| Some((entry.deletion_time, entry.operation?)))
+            .max()
+            .map(|(_, operation)| operation);
+        Ok(operation)
+    }
+
     /// Return the base directory of this trash.
     pub fn base_dir(&self) -> &Utf8Path {
         &self.base_dir
     }
 
     /// Return an iterator on the entries of this trash.
-    pub fn entries(&self) -> Result<impl Iterator<Item = Result<TrashEntry>>> {
-        let entries = self.trashinfo_paths()?.map(|path| self.new_entry(&path));
+    ///
+    /// Broken entries are returned as errors, which tell the `.trashinfo` file they come from.
+    pub fn entries(&self) -> Result<impl Iterator<Item = Result<TrashEntry, TrashEntryError>>> {
+        let entries = self.trashinfo_paths()?.map(|trashinfo_path| {
+            self.new_entry(&trashinfo_path)
+                .map_err(|source| TrashEntryError {
+                    identifier: identifier_of_trashinfo(&trashinfo_path),
+                    trashinfo_path,
+                    source,
+                })
+        });
+        Ok(entries)
+    }
+
+    /// Return the entry with the given identifier in this trash.
+    pub fn entry(&self, identifier: impl AsRef<str>) -> Result<TrashEntry> {
+        let identifier = identifier.as_ref();
+        let trashinfo_path = self.trashinfo_path(identifier);
+        if !trashinfo_path.exists() {
+            bail!("identifier {identifier} not found in trash");
+        }
+        self.new_entry(trashinfo_path)
+    }
+
+    /// Return an iterator on the entries of this trash that match the given query.
+    ///
+    /// Entries in error are returned as is.
+    pub fn query<'a>(
+        &'a self,
+        query: &TrashQuery,
+    ) -> Result<impl Iterator<Item = Result<TrashEntry, TrashEntryError>> + use<'a>> {
+        let query = query.clone();
+        let entries = self
+            .entries()?
+            .filter(move |entry| entry.as_ref().map_or(true, |entry| query.matches(entry)));
         Ok(entries)
     }
 
+    /// Return the versions of the original paths of the entries of this trash that match the given query.
+    ///
+    /// The entries are grouped by original path,
+    /// and the versions of each path are sorted by deletion time, most recent first.
+    /// Versions deleted in the same second are sorted by identifier, the most recently created first.
+    /// Entries in error are ignored.
+    pub fn versions(&self, query: &TrashQuery) -> Result<BTreeMap<Utf8PathBuf, Vec<TrashEntry>>> {
+        let mut versions = BTreeMap::<_, Vec<_>>::new();
+        for entry in self.query(query)?.filter_map(|entry| entry.ok()) {
+            versions
+                .entry(entry.original_path.clone())
+                .or_default()
+                .push(entry);
+        }
+        for entries in versions.values_mut() {
+            // NOTE: The identifiers of the versions of a path only differ by their numeric suffix,
+            // so a longer identifier was created later
+            entries.sort_by(|entry1, entry2| {
+                (
+                    entry2.deletion_time,
+                    entry2.identifier.len(),
+                    &entry2.identifier,
+                )
+                    .cmp(&(
+                        entry1.deletion_time,
+                        entry1.identifier.len(),
+                        &entry1.identifier,
+                    ))
+            });
+        }
+        Ok(versions)
+    }
+
+    /// Locate the given original path in this trash.
+    ///
+    /// The path is either the original path of an entry,
+    /// or a path inside the original path of an entry that is a directory.
+    /// Return the matching entry, along with the path of the corresponding file in the trash, if any.
+    /// If several entries match, the most recent version of the closest one is returned.
+    /// Entries in error are ignored.
+    pub fn locate(&self, path: impl AsRef<Utf8Path>) -> Result<Option<(TrashEntry, Utf8PathBuf)>> {
+        let path = path.as_ref();
+        let versions = self.versions(&TrashQuery::new())?;
+        for original_path in path.ancestors() {
+            let Some(entries) = versions.get(original_path) else {
+                continue;
+            };
+            for entry in entries {
+                if let Some(file_path) = entry.file_path_of(path)
+                    && file_path.symlink_metadata().is_ok()
+                {
+                    return Ok(Some((entry.clone(), file_path)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
     /// Return an iterator on the trash info files in this trash.
     fn trashinfo_paths(&self) -> Result<impl Iterator<Item = Utf8PathBuf>> {
         fn is_trashinfo_file(path: impl AsRef<Utf8Path>) -> bool {
@@ -145,22 +350,25 @@ impl Trash {
     fn new_entry(&self, trashinfo_path: impl AsRef<Utf8Path>) -> Result<TrashEntry> {
         let trashinfo_path = trashinfo_path.as_ref();
         // Determine identifier
-        let file_name = trashinfo_path
-            .file_name()
-            .expect("trashinfo path has no file name");
-        // NOTE: Utf8Path has no `base_name` method, so we strip the extension ourselves
-        let identifier = String::from(&file_name[..file_name.len() - (1 + EXT_TRASHINFO.len())]);
+        let identifier = identifier_of_trashinfo(trashinfo_path);
         // Read trashinfo file
-        let trashinfo_file = fs::File::open(trashinfo_path)?;
         let TrashInfo {
             path: original_path,
             deletion_time,
-        } = TrashInfo::read_from(&mut io::BufReader::new(trashinfo_file))?;
+            operation,
+        } = self.read_trashinfo(&identifier)?;
         // Examine file
         let file_path = self.files_dir.join(&identifier);
         let file_metadata = file_path
             .symlink_metadata()
             .with_context(|| format!("cannot get metadata of file {file_path}"))?;
+        let file_type = if file_metadata.is_dir() {
+            TrashFileType::Dir
+        } else if file_metadata.is_symlink() {
+            TrashFileType::Symlink
+        } else {
+            TrashFileType::File
+        };
         let size = if file_metadata.is_dir() {
             // The file is a directory
             // Get its size from the cached directory sizes
@@ -186,10 +394,57 @@ impl Trash {
             original_path,
             deletion_time,
             size,
+            file_type,
+            trash_dir: self.base_dir.clone(),
+            operation,
         };
         Ok(entry)
     }
 
+    /// Read the `.trashinfo` file for the given identifier.
+    ///
+    /// The original path is made absolute (see [`absolute_original_path`](Self::absolute_original_path)).
+    fn read_trashinfo(&self, identifier: &str) -> Result<TrashInfo> {
+        let trashinfo_path = self.trashinfo_path(identifier);
+        let trashinfo_file = fs::File::open(&trashinfo_path)
+            .with_context(|| format!("cannot open trashinfo file {trashinfo_path}"))?;
+        let mut trashinfo = TrashInfo::read_from(&mut io::BufReader::new(trashinfo_file))
+            .with_context(|| format!("cannot read trashinfo file {trashinfo_path}"))?;
+        trashinfo.path = self.absolute_original_path(trashinfo.path);
+        Ok(trashinfo)
+    }
+
+    /// Return the absolute form of the given original path, read from a `.trashinfo` file.
+    ///
+    /// # Specification
+    ///
+    /// In a top directory trash, i.e. `$topdir/.Trash/$uid` or `$topdir/.Trash-$uid`,
+    /// the original path may be relative to the top directory.
+    /// In any other trash, such as the home trash, it is returned unchanged.
+    fn absolute_original_path(&self, path: Utf8PathBuf) -> Utf8PathBuf {
+        if path.is_absolute() {
+            return path;
+        }
+        let parent = self.base_dir.parent();
+        let top_dir = if parent.and_then(Utf8Path::file_name) == Some(".Trash") {
+            // $topdir/.Trash/$uid
+            parent.and_then(Utf8Path::parent)
+        } else if self
+            .base_dir
+            .file_name()
+            .is_some_and(|name| name.starts_with(".Trash-"))
+        {
+            // $topdir/.Trash-$uid
+            parent
+        } else {
+            None
+        };
+        match top_dir {
+            Some(top_dir) => top_dir.join(path),
+            None => path,
+        }
+    }
+
     fn create_dirs(&self) -> Result<()> {
         for dir in [&self.base_dir, &self.info_dir, &self.files_dir] {
             fs::create_dir_all(dir)
@@ -199,24 +454,89 @@ impl Trash {
     }
 
     pub fn put(&self, path: impl AsRef<Utf8Path>) -> Result<TrashPutReport> {
-        let path = path.as_ref().canonicalize_utf8()?;
+        let path = canonicalize_parent(path.as_ref())?;
         let deletion_time = Local::now().naive_local();
-        let trashinfo = TrashInfo {
-            path: path.to_owned(),
-            deletion_time,
+        // NOTE: Computing the size walks the whole tree of a directory, so a real put doesn't do it
+        let size = if self.dry_run {
+            disk_size(&path).ok()
+        } else {
+            None
+        };
+        let identifier = if self.dry_run {
+            self.check_same_file_system(&path)?;
+            let identifier = self.next_identifier(&path);
+            self.dry_run_identifiers
+                .borrow_mut()
+                .insert(identifier.clone());
+            identifier
+        } else {
+            let trashinfo = TrashInfo {
+                path: path.to_owned(),
+                deletion_time,
+                operation: self.operation.clone(),
+            };
+            self.create_dirs()?;
+            let (identifier, trashinfo_file) = self.open_new_trashinfo_file(&path)?;
+            trashinfo.write_to(&mut io::BufWriter::new(trashinfo_file))?;
+            let file_path = self.files_dir.join(&identifier);
+            fs::rename(&path, &file_path)?;
+            identifier
         };
-        self.create_dirs()?;
-        let (identifier, trashinfo_file) = self.open_new_trashinfo_file(&path)?;
-        trashinfo.write_to(&mut io::BufWriter::new(trashinfo_file))?;
-        let file_path = self.files_dir.join(identifier);
-        fs::rename(&path, &file_path)?;
         let report = TrashPutReport {
+            identifier,
             path,
             deletion_time,
+            size,
         };
         Ok(report)
     }
 
+    /// Check that the given path is on the same file system as this trash.
+    ///
+    /// The path is moved to the trash with a rename, which fails across file systems.
+    fn check_same_file_system(&self, path: impl AsRef<Utf8Path>) -> Result<()> {
+        let path = path.as_ref();
+        let device = path
+            .symlink_metadata()
+            .with_context(|| format!("cannot get metadata of file {path}"))?
+            .dev();
+        // NOTE: The trash may not exist yet, so we check its closest existing ancestor
+        let trash_dir = self
+            .files_dir
+            .ancestors()
+            .find(|dir| dir.exists())
+            .expect("no ancestor of the trash directory exists");
+        let trash_device = trash_dir
+            .metadata()
+            .with_context(|| format!("cannot get metadata of directory {trash_dir}"))?
+            .dev();
+        if device != trash_device {
+            bail!("file {path} is not on the same file system as the trash");
+        }
+        Ok(())
+    }
+
+    /// Return the identifier that would be used for the given path if it was put in this trash now.
+    ///
+    /// The identifiers already handed out in dry-run mode are considered used.
+    fn next_identifier(&self, path: impl AsRef<Utf8Path>) -> String {
+        let base_identifier = identifier(path);
+        (0_u16..)
+            .map(|number| {
+                if number == 0 {
+                    base_identifier.clone()
+                } else {
+                    format!("{base_identifier}_{number}")
+                }
+            })
+            .find(|identifier| {
+                !self.trashinfo_path(identifier).exists()
+                    && !self.dry_run_identifiers.borrow().contains(identifier)
+            })
+            // TODO: Handle overflow
+            .expect("no identifier available")
+    }
+
     /// Create and open a new `.trashinfo` file in this trash for the given path.
     fn open_new_trashinfo_file(&self, path: impl AsRef<Utf8Path>) -> Result<(String, fs::File)> {
         let path = path.as_ref();
@@ -228,7 +548,7 @@ impl Trash {
             } else {
                 format!("{base_identifier}_{number}")
             };
-            let trashinfo_path = self.info_dir.join(format!("{identifier}.{EXT_TRASHINFO}"));
+            let trashinfo_path = self.trashinfo_path(&identifier);
             match fs::OpenOptions::new()
                 .create_new(true)
                 .write(true)
@@ -257,16 +577,12 @@ impl Trash {
     pub fn restore(&self, identifier: impl AsRef<str>) -> Result<TrashRestoreReport> {
         let identifier = identifier.as_ref();
         // Read trashinfo
-        let trashinfo_path = self.info_dir.join(format!("{identifier}.{EXT_TRASHINFO}"));
+        let trashinfo_path = self.trashinfo_path(identifier);
         let TrashInfo {
             path: original_path,
             deletion_time,
-        } = {
-            let mut trashinfo_file = fs::File::open(&trashinfo_path)
-                .with_context(|| format!("cannot open trashinfo file {trashinfo_path}"))?;
-            TrashInfo::read_from(&mut trashinfo_file)
-                .with_context(|| format!("cannot read trashinfo file {trashinfo_path}"))?
-        };
+            ..
+        } = self.read_trashinfo(identifier)?;
         // Check if original path is available
         if original_path.exists() {
             bail!("file {original_path} already exists");
@@ -276,32 +592,156 @@ impl Trash {
         if !file_path.exists() {
             bail!("file {file_path} not found");
         }
-        // Move trash file to original path
-        fs::rename(&file_path, &original_path)
-            .with_context(|| format!("cannot move file {file_path} to {original_path}"))?;
-        // Remove trashinfo file
-        fs::remove_file(&trashinfo_path)
-            .with_context(|| format!("cannot remove trashinfo file {trashinfo_path}"))?;
-        // TODO: If file is a directory, remove it from dir sizes
+        if self.dry_run {
+            // Check if original directory exists
+            if let Some(original_dir) = original_path.parent()
+                && !original_dir.is_dir()
+            {
+                bail!("directory {original_dir} not found");
+            }
+        } else {
+            // Move trash file to original path
+            fs::rename(&file_path, &original_path)
+                .with_context(|| format!("cannot move file {file_path} to {original_path}"))?;
+            // Remove trashinfo file
+            fs::remove_file(&trashinfo_path)
+                .with_context(|| format!("cannot remove trashinfo file {trashinfo_path}"))?;
+            // Update dir sizes
+            // NOTE: The file has been moved, and the directory sizes are only a cache,
+            // so errors are not reported
+            let _ = self.remove_dir_size(identifier);
+        }
         let report = TrashRestoreReport {
+            identifier: identifier.to_string(),
             path: original_path,
             deletion_time,
         };
         Ok(report)
     }
 
-    pub fn empty(&self) -> Result<TrashEmptyReport> {
-        let mut entry_count = 0_usize;
-        // Remove trashinfo files
-        for trashinfo_path in self.trashinfo_paths()? {
+    /// Restore the given path from inside the entry with the given identifier.
+    ///
+    /// The entry must be a directory, and the path must be strictly inside its original path.
+    /// Only the corresponding file is moved back to the path, creating its parent directories if needed;
+    /// the rest of the directory stays in this trash, and its cached size is updated.
+    pub fn extract(
+        &self,
+        identifier: impl AsRef<str>,
+        path: impl AsRef<Utf8Path>,
+    ) -> Result<TrashRestoreReport> {
+        let identifier = identifier.as_ref();
+        let path = path.as_ref();
+        // Read trashinfo
+        let TrashInfo {
+            path: original_path,
+            deletion_time,
+            ..
+        } = self.read_trashinfo(identifier)?;
+        // Determine path relative to original path
+        let relative_path = path
+            .strip_prefix(&original_path)
+            .ok()
+            .filter(|relative_path| {
+                !relative_path.as_str().is_empty()
+                    && relative_path
+                        .components()
+                        .all(|component| matches!(component, Utf8Component::Normal(_)))
+            })
+            .with_context(|| format!("file {path} is not inside {original_path}"))?;
+        // Check if path is available
+        if path.symlink_metadata().is_ok() {
+            bail!("file {path} already exists");
+        }
+        // Determine trash file
+        let file_path = self.files_dir.join(identifier).join(relative_path);
+        if file_path.symlink_metadata().is_err() {
+            bail!("file {file_path} not found");
+        }
+        if !self.dry_run {
+            // Create parent directories
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("cannot create directory {dir}"))?;
+            }
+            // Move trash file to path
+            fs::rename(&file_path, path)
+                .with_context(|| format!("cannot move file {file_path} to {path}"))?;
+            // Update dir sizes
+            // NOTE: The file has been moved, and the directory sizes are only a cache,
+            // so errors are not reported
+            let _ = self.update_dir_size(identifier);
+        }
+        let report = TrashRestoreReport {
+            identifier: identifier.to_string(),
+            path: path.to_owned(),
+            deletion_time,
+        };
+        Ok(report)
+    }
+
+    /// Remove the entry with the given identifier from this trash permanently.
+    pub fn remove(&self, identifier: impl AsRef<str>) -> Result<TrashRemoveReport> {
+        let identifier = identifier.as_ref();
+        // Read trashinfo
+        let trashinfo_path = self.trashinfo_path(identifier);
+        let TrashInfo {
+            path: original_path,
+            deletion_time,
+            ..
+        } = self.read_trashinfo(identifier)?;
+        // Determine trash file
+        let file_path = self.files_dir.join(identifier);
+        let file_metadata = file_path
+            .symlink_metadata()
+            .with_context(|| format!("file {file_path} not found"))?;
+        // NOTE: The size is only reported, so failing to compute it must not prevent removing the file
+        let size = disk_size(&file_path).unwrap_or_default();
+        if !self.dry_run {
+            // Remove trash file
+            // NOTE: The trash file is removed first, so that an interruption leaves its trashinfo file
+            if file_metadata.is_dir() {
+                fs::remove_dir_all(&file_path)
+                    .with_context(|| format!("cannot remove directory {file_path}"))?;
+            } else {
+                fs::remove_file(&file_path)
+                    .with_context(|| format!("cannot remove file {file_path}"))?;
+            }
+            // Remove trashinfo file
             fs::remove_file(&trashinfo_path)
                 .with_context(|| format!("cannot remove trashinfo file {trashinfo_path}"))?;
+            // Update dir sizes
+            // NOTE: The file has been removed, and the directory sizes are only a cache,
+            // so errors are not reported
+            let _ = self.remove_dir_size(identifier);
+        }
+        let report = TrashRemoveReport {
+            identifier: identifier.to_string(),
+            path: original_path,
+            deletion_time,
+            size,
+        };
+        Ok(report)
+    }
+
+    pub fn empty(&self) -> Result<TrashEmptyReport> {
+        let mut entry_count = 0_usize;
+        let mut size = 0_u64;
+        if !self.dry_run {
+            // Remove trashinfo files
+            for trashinfo_path in self.trashinfo_paths()? {
+                fs::remove_file(&trashinfo_path)
+                    .with_context(|| format!("cannot remove trashinfo file {trashinfo_path}"))?;
+            }
         }
         // Remove trash files
-        for dir_entry in self.files_dir.read_dir_utf8()? {
+        for dir_entry in self.files_dir.read_dir_utf8_or_empty()? {
             let dir_entry = dir_entry.context("cannot read contents of trash files directory")?;
             let file_path = dir_entry.path();
-            if file_path.is_dir() {
+            // NOTE: The size is only reported, so failing to compute it must not stop emptying the trash
+            size += disk_size(file_path).unwrap_or_default();
+            if self.dry_run {
+                // Nothing to do
+            } else if file_path.is_dir() {
                 fs::remove_dir_all(file_path)
                     .with_context(|| format!("cannot remove directory {file_path}"))?;
             } else {
@@ -312,16 +752,26 @@ impl Trash {
         }
         // Remove dir sizes file
         let directorysizes_file = &self.directorysizes_file;
-        fs::remove_file(directorysizes_file)
-            .with_context(|| format!("cannot remove directorysizes file {directorysizes_file}"))?;
-        let report = TrashEmptyReport {
-            entry_count,
-            // TODO: Compute size
-            size: 0,
-        };
+        if !self.dry_run && directorysizes_file.exists() {
+            fs::remove_file(directorysizes_file).with_context(|| {
+                format!("cannot remove directorysizes file {directorysizes_file}")
+            })?;
+        }
+        let report = TrashEmptyReport { entry_count, size };
         Ok(report)
     }
 
+    /// Return whether the given file name is that of a temporary file used to replace the `directorysizes` file.
+    fn is_directorysizes_temp_file(name: &str) -> bool {
+        name.starts_with(DIRECTORYSIZES_TEMP_PREFIX) && name.ends_with(DIRECTORYSIZES_TEMP_SUFFIX)
+    }
+
+    /// Return the path of the `.trashinfo` file for the given identifier.
+    fn trashinfo_path(&self, identifier: impl AsRef<str>) -> Utf8PathBuf {
+        let identifier = identifier.as_ref();
+        self.info_dir.join(format!("{identifier}.{EXT_TRASHINFO}"))
+    }
+
     fn dir_sizes(&self) -> &DirSizes {
         self.dir_sizes.get_or_init(|| {
             self.load_dir_sizes()
@@ -334,6 +784,65 @@ impl Trash {
         let mut file = fs::File::open(&self.directorysizes_file)?;
         dir_sizes::read_from(&mut file)
     }
+
+    /// Record the current size of the directory entry with the given identifier in the `directorysizes` file.
+    // NOTE: The cached directory sizes of this trash are not updated
+    fn update_dir_size(&self, identifier: impl AsRef<str>) -> Result<()> {
+        let identifier = identifier.as_ref();
+        let file_path = self.files_dir.join(identifier);
+        let size =
+            disk_size(&file_path).with_context(|| format!("cannot compute size of {file_path}"))?;
+        let trashinfo_path = self.trashinfo_path(identifier);
+        let mtime = trashinfo_path
+            .metadata()
+            .with_context(|| format!("cannot get metadata of trashinfo file {trashinfo_path}"))?
+            .mtime() as u64;
+        // NOTE: The directorysizes file may not exist yet
+        let mut dir_sizes = self.load_dir_sizes().unwrap_or_default();
+        let dir_size = DirSize {
+            name: identifier.to_string(),
+            size,
+            mtime,
+        };
+        dir_sizes.insert(identifier.to_string(), dir_size);
+        self.write_dir_sizes(&dir_sizes)
+    }
+
+    /// Remove the directory entry with the given identifier from the `directorysizes` file, if present.
+    // NOTE: The cached directory sizes of this trash are not updated
+    fn remove_dir_size(&self, identifier: impl AsRef<str>) -> Result<()> {
+        // NOTE: The directorysizes file may not exist
+        let mut dir_sizes = self.load_dir_sizes().unwrap_or_default();
+        if dir_sizes.remove(identifier.as_ref()).is_some() {
+            self.write_dir_sizes(&dir_sizes)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the `directorysizes` file with the given directory sizes.
+    ///
+    /// The file is replaced atomically, as mandated by the spec.
+    fn write_dir_sizes(&self, dir_sizes: &DirSizes) -> Result<()> {
+        let directorysizes_file = &self.directorysizes_file;
+        // NOTE: The temporary file has a unique name, so that concurrent writers don't clash,
+        // and it is removed if it cannot be written
+        let mut temp_file = tempfile::Builder::new()
+            .prefix(DIRECTORYSIZES_TEMP_PREFIX)
+            .suffix(DIRECTORYSIZES_TEMP_SUFFIX)
+            .tempfile_in(&self.base_dir)
+            .with_context(|| format!("cannot create temporary file in {}", self.base_dir))?;
+        let temp_path = temp_file.path().display().to_string();
+        {
+            let mut writer = io::BufWriter::new(temp_file.as_file_mut());
+            dir_sizes::write_to(&mut writer, dir_sizes)
+                .and_then(|()| Ok(writer.flush()?))
+                .with_context(|| format!("cannot write file {temp_path}"))?;
+        }
+        temp_file
+            .persist(directorysizes_file)
+            .with_context(|| format!("cannot replace directorysizes file {directorysizes_file}"))?;
+        Ok(())
+    }
 }
 
 impl Default for Trash {
@@ -359,6 +868,162 @@ impl TrashEntry {
     pub fn size(&self) -> u64 {
         self.size
     }
+
+    pub fn file_type(&self) -> TrashFileType {
+        self.file_type
+    }
+
+    /// Return the base directory of the trash this entry belongs to.
+    pub fn trash_dir(&self) -> &Utf8Path {
+        &self.trash_dir
+    }
+
+    /// Return the path of the file of this entry, in the `files` directory of the trash.
+    ///
+    /// This file should not be modified.
+    pub fn file_path(&self) -> Utf8PathBuf {
+        self.trash_dir.join(DIR_FILES).join(&self.identifier)
+    }
+
+    /// Return the path of the file in the trash corresponding to the given original path.
+    ///
+    /// The given path is either the original path of this entry,
+    /// or a path inside it if this entry is a directory.
+    /// Return `None` if the given path is neither,
+    /// or if it contains components such as `..` that could lead outside this entry.
+    /// The returned file may not exist.
+    pub fn file_path_of(&self, path: impl AsRef<Utf8Path>) -> Option<Utf8PathBuf> {
+        let path = path.as_ref();
+        let relative_path = path.strip_prefix(&self.original_path).ok()?;
+        if relative_path
+            .components()
+            .any(|component| !matches!(component, Utf8Component::Normal(_)))
+        {
+            return None;
+        }
+        if relative_path.as_str().is_empty() {
+            Some(self.file_path())
+        } else {
+            Some(self.file_path().join(relative_path))
+        }
+    }
+
+    /// Return the path of the `.trashinfo` file of this entry, in the `info` directory of the trash.
+    pub fn trashinfo_path(&self) -> Utf8PathBuf {
+        self.trash_dir
+            .join(DIR_INFO)
+            .join(format!("{}.{EXT_TRASHINFO}", self.identifier))
+    }
+
+    /// Return the metadata of the file of this entry.
+    ///
+    /// Symbolic links are not followed.
+    pub fn metadata(&self) -> io::Result<fs::Metadata> {
+        self.file_path().symlink_metadata()
+    }
+
+    /// Compute the actual size of the file of this entry.
+    ///
+    /// Unlike [`size`](Self::size), which relies on the cached directory sizes for directories,
+    /// this method walks directories recursively.
+    pub fn computed_size(&self) -> io::Result<u64> {
+        disk_size(self.file_path())
+    }
+
+    /// Return the operation that put this entry in the trash, if recorded.
+    pub fn operation(&self) -> Option<&str> {
+        self.operation.as_deref()
+    }
+}
+
+impl TrashEntryError {
+    /// Return the identifier of the broken entry.
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Return the path of the `.trashinfo` file of the broken entry.
+    pub fn trashinfo_path(&self) -> &Utf8Path {
+        &self.trashinfo_path
+    }
+
+    /// Return the reason why the entry is broken.
+    pub fn reason(&self) -> &anyhow::Error {
+        &self.source
+    }
+}
+
+impl Display for TrashEntryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "broken entry {} (trashinfo file {}): {:#}",
+            self.identifier, self.trashinfo_path, self.source
+        )
+    }
+}
+
+// NOTE: The source is not exposed through `Error::source`, as it is already part of the message
+impl std::error::Error for TrashEntryError {}
+
+impl Display for TrashFileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::File => "file",
+            Self::Dir => "dir",
+            Self::Symlink => "symlink",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Compute the size of the given path on disk.
+///
+/// If the path is a directory, its size is the sum of the sizes of the files it contains, recursively.
+/// Symbolic links are not followed.
+pub fn disk_size(path: impl AsRef<Utf8Path>) -> io::Result<u64> {
+    let path = path.as_ref();
+    let metadata = path.symlink_metadata()?;
+    if metadata.is_dir() {
+        let mut size = 0;
+        for dir_entry in path.read_dir_utf8()? {
+            size += disk_size(dir_entry?.path())?;
+        }
+        Ok(size)
+    } else {
+        Ok(metadata.len())
+    }
+}
+
+/// Return the canonical form of the given path, without following it if it is a symbolic link.
+///
+/// Only the parent directory is canonicalized, so that a symbolic link is put in the trash
+/// rather than its target.
+fn canonicalize_parent(path: &Utf8Path) -> io::Result<Utf8PathBuf> {
+    let Some(file_name) = path.file_name() else {
+        return path.canonicalize_utf8();
+    };
+    // NOTE: Fail like `canonicalize` if the file does not exist
+    path.symlink_metadata()?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_str().is_empty() => parent,
+        _ => Utf8Path::new("."),
+    };
+    Ok(parent.canonicalize_utf8()?.join(file_name))
+}
+
+/// Return the identifier of the entry of the given `.trashinfo` file.
+///
+/// # Panics
+///
+/// This function panics if the given path has no file name.
+fn identifier_of_trashinfo(trashinfo_path: impl AsRef<Utf8Path>) -> String {
+    let trashinfo_path = trashinfo_path.as_ref();
+    let file_name = trashinfo_path
+        .file_name()
+        .expect("trashinfo path has no file name");
+    // NOTE: Utf8Path has no `base_name` method, so we strip the extension ourselves
+    String::from(&file_name[..file_name.len() - (1 + EXT_TRASHINFO.len())])
 }
 
 /// Compute an identifier for the given path.
@@ -421,5 +1086,395 @@ mod tests {
         let entry = entries.first().unwrap().as_ref().unwrap();
         assert_eq!(entry.original_path, test_file_canonical_path);
         assert_eq!(entry.size, test_file_size);
+        assert_eq!(entry.file_type, TrashFileType::File);
+        assert_eq!(entry.trash_dir, trash.base_dir());
+        assert_eq!(entry.file_path(), trash.files_dir.join(&entry.identifier));
+        assert!(entry.trashinfo_path().is_file());
+        assert_eq!(entry.computed_size().unwrap(), test_file_size);
+        assert_eq!(trash.entry(&entry.identifier).unwrap(), *entry);
+    }
+
+    #[test]
+    fn test_entries_relative_path() {
+        let top_dir = TempDir::new().unwrap();
+        let top_dir_path = Utf8Path::from_path(top_dir.path()).unwrap();
+        for base_dir in [".Trash-1000", ".Trash/1000"] {
+            let trash = Trash::new(top_dir_path.join(base_dir));
+            trash.create_dirs().unwrap();
+            fs::write(
+                trash.trashinfo_path("a.txt"),
+                "[Trash Info]\nPath=Documents/a.txt\nDeletionDate=2025-09-08T22:11:09\n",
+            )
+            .unwrap();
+            fs::write(trash.files_dir.join("a.txt"), "abc").unwrap();
+            let entry = trash.entry("a.txt").unwrap();
+            assert_eq!(entry.original_path, top_dir_path.join("Documents/a.txt"));
+        }
+        // NOTE: Other trashes don't resolve relative paths
+        let trash = Trash::new(top_dir_path.join("Trash"));
+        trash.create_dirs().unwrap();
+        fs::write(
+            trash.trashinfo_path("a.txt"),
+            "[Trash Info]\nPath=Documents/a.txt\nDeletionDate=2025-09-08T22:11:09\n",
+        )
+        .unwrap();
+        fs::write(trash.files_dir.join("a.txt"), "abc").unwrap();
+        let entry = trash.entry("a.txt").unwrap();
+        assert_eq!(entry.original_path, "Documents/a.txt");
+    }
+
+    #[test]
+    fn test_put_symlink() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path())
+            .unwrap()
+            .canonicalize_utf8()
+            .unwrap();
+        let target_path = test_dir_path.join("target.txt");
+        let link_path = test_dir_path.join("link.txt");
+        fs::write(&target_path, "abc").unwrap();
+        std::os::unix::fs::symlink(&target_path, &link_path).unwrap();
+        let report = trash.put(&link_path).unwrap();
+        assert_eq!(report.path, link_path);
+        assert!(target_path.is_file());
+        assert!(link_path.symlink_metadata().is_err());
+        let entry = trash.entry(&report.identifier).unwrap();
+        assert_eq!(entry.file_type, TrashFileType::Symlink);
+    }
+
+    #[test]
+    fn test_put_file_dry_run() {
+        let trash = new_test_trash().with_dry_run(true);
+        let test_file = NamedTempFile::new("test").unwrap();
+        test_file.write_str("abc").unwrap();
+        let test_file_path = Utf8Path::from_path(test_file.path()).unwrap();
+        let report = trash.put(test_file_path).unwrap();
+        assert_eq!(report.identifier, "test");
+        assert_eq!(report.size, Some(3));
+        assert!(test_file_path.exists());
+        assert!(!trash.base_dir().exists());
+    }
+
+    #[test]
+    fn test_put_file_dry_run_identifier_conflict() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_file_path = Utf8Path::from_path(test_dir.path()).unwrap().join("test");
+        fs::write(&test_file_path, "abc").unwrap();
+        trash.put(&test_file_path).unwrap();
+        fs::write(&test_file_path, "def").unwrap();
+        let report = trash.with_dry_run(true).put(&test_file_path).unwrap();
+        assert_eq!(report.identifier, "test_1");
+    }
+
+    #[test]
+    fn test_put_files_dry_run_same_name() {
+        let trash = new_test_trash().with_dry_run(true);
+        let test_dir1 = TempDir::new().unwrap();
+        let test_dir2 = TempDir::new().unwrap();
+        let test_file_path1 = Utf8Path::from_path(test_dir1.path()).unwrap().join("test");
+        let test_file_path2 = Utf8Path::from_path(test_dir2.path()).unwrap().join("test");
+        fs::write(&test_file_path1, "abc").unwrap();
+        fs::write(&test_file_path2, "def").unwrap();
+        let report1 = trash.put(&test_file_path1).unwrap();
+        let report2 = trash.put(&test_file_path2).unwrap();
+        assert_eq!(report1.identifier, "test");
+        assert_eq!(report2.identifier, "test_1");
+    }
+
+    #[test]
+    fn test_restore_dry_run() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_file_path = Utf8Path::from_path(test_dir.path()).unwrap().join("test");
+        fs::write(&test_file_path, "abc").unwrap();
+        let test_file_canonical_path = test_file_path.canonicalize_utf8().unwrap();
+        let put_report = trash.put(&test_file_path).unwrap();
+        let trash = trash.with_dry_run(true);
+        let report = trash.restore(&put_report.identifier).unwrap();
+        assert_eq!(report.path, test_file_canonical_path);
+        assert!(!test_file_path.exists());
+        assert_eq!(trash.entries().unwrap().count(), 1);
+        // Restoring over an existing file is predicted to fail
+        fs::write(&test_file_path, "def").unwrap();
+        assert!(trash.restore(&put_report.identifier).is_err());
+    }
+
+    #[test]
+    fn test_empty_dry_run() {
+        let trash = new_test_trash();
+        let test_file = NamedTempFile::new("test").unwrap();
+        test_file.write_str("abc").unwrap();
+        trash
+            .put(Utf8Path::from_path(test_file.path()).unwrap())
+            .unwrap();
+        let trash = trash.with_dry_run(true);
+        let report = trash.empty().unwrap();
+        assert_eq!(report.entry_count, 1);
+        assert_eq!(report.size, 3);
+        assert_eq!(trash.entries().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_put_with_operation() {
+        let trash = new_test_trash();
+        assert_eq!(trash.last_operation().unwrap(), None);
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path()).unwrap();
+        let test_file_paths = ["test1", "test2", "test3"].map(|name| test_dir_path.join(name));
+        for test_file_path in &test_file_paths {
+            fs::write(test_file_path, "abc").unwrap();
+        }
+        let trash = trash.with_operation(Some(String::from("op1")));
+        trash.put(&test_file_paths[0]).unwrap();
+        let trash = trash.with_operation(Some(String::from("op2")));
+        trash.put(&test_file_paths[1]).unwrap();
+        trash.put(&test_file_paths[2]).unwrap();
+        assert_eq!(trash.last_operation().unwrap().as_deref(), Some("op2"));
+        let entries = trash
+            .query(&TrashQuery::new().operation("op2"))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn test_versions() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path()).unwrap();
+        let test_file1_path = test_dir_path.join("test1");
+        let test_file2_path = test_dir_path.join("test2");
+        for contents in ["a", "ab", "abc"] {
+            fs::write(&test_file1_path, contents).unwrap();
+            trash.put(&test_file1_path).unwrap();
+        }
+        fs::write(&test_file2_path, "abc").unwrap();
+        trash.put(&test_file2_path).unwrap();
+        let versions = trash.versions(&TrashQuery::new()).unwrap();
+        assert_eq!(versions.len(), 2);
+        let test_file1_versions = versions
+            .get(&test_dir_path.canonicalize_utf8().unwrap().join("test1"))
+            .unwrap();
+        assert_eq!(test_file1_versions.len(), 3);
+        assert!(
+            test_file1_versions
+                .is_sorted_by(|entry1, entry2| entry1.deletion_time >= entry2.deletion_time)
+        );
+    }
+
+    #[test]
+    fn test_versions_same_deletion_time() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path()).unwrap();
+        let test_file_path = test_dir_path.join("test");
+        let mut identifiers = Vec::new();
+        for _ in 0..11 {
+            fs::write(&test_file_path, "abc").unwrap();
+            identifiers.push(trash.put(&test_file_path).unwrap().identifier);
+        }
+        // NOTE: The versions are put in the trash in the same second
+        for identifier in &identifiers {
+            let trashinfo_path = trash.trashinfo_path(identifier);
+            let trashinfo = fs::read_to_string(&trashinfo_path).unwrap();
+            let trashinfo = trashinfo
+                .lines()
+                .map(|line| {
+                    if line.starts_with("DeletionDate=") {
+                        "DeletionDate=2025-02-17T13:14:15"
+                    } else {
+                        line
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            fs::write(&trashinfo_path, trashinfo).unwrap();
+        }
+        let versions = trash.versions(&TrashQuery::new()).unwrap();
+        let version_identifiers = versions
+            .values()
+            .flatten()
+            .map(|entry| entry.identifier.clone())
+            .collect::<Vec<_>>();
+        identifiers.reverse();
+        assert_eq!(version_identifiers, identifiers);
+    }
+
+    #[test]
+    fn test_remove() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path()).unwrap();
+        let test_subdir_path = test_dir_path.join("test");
+        fs::create_dir(&test_subdir_path).unwrap();
+        fs::write(test_subdir_path.join("test.txt"), "abc").unwrap();
+        let put_report = trash.put(&test_subdir_path).unwrap();
+        trash.rebuild_dir_sizes().unwrap();
+        let report = trash
+            .clone()
+            .with_dry_run(true)
+            .remove(&put_report.identifier)
+            .unwrap();
+        assert_eq!(report.size, 3);
+        assert_eq!(trash.entries().unwrap().count(), 1);
+        trash.remove(&put_report.identifier).unwrap();
+        assert_eq!(trash.entries().unwrap().count(), 0);
+        assert!(!trash.files_dir.join(&put_report.identifier).exists());
+        assert!(trash.load_dir_sizes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_query_size_uncached_dir() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path()).unwrap();
+        let test_subdir_path = test_dir_path.join("test");
+        fs::create_dir(&test_subdir_path).unwrap();
+        fs::write(test_subdir_path.join("test.txt"), "abc").unwrap();
+        trash.put(&test_subdir_path).unwrap();
+        assert_eq!(
+            trash
+                .query(&TrashQuery::new().larger_than(2))
+                .unwrap()
+                .count(),
+            1
+        );
+        assert_eq!(
+            trash
+                .query(&TrashQuery::new().smaller_than(3))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_restore_dir_sizes() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path()).unwrap();
+        let test_subdir_path = test_dir_path.join("test");
+        fs::create_dir(&test_subdir_path).unwrap();
+        fs::write(test_subdir_path.join("test.txt"), "abc").unwrap();
+        let put_report = trash.put(&test_subdir_path).unwrap();
+        trash.rebuild_dir_sizes().unwrap();
+        assert!(
+            trash
+                .load_dir_sizes()
+                .unwrap()
+                .contains_key(&put_report.identifier)
+        );
+        trash.restore(&put_report.identifier).unwrap();
+        assert!(trash.load_dir_sizes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_locate() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path())
+            .unwrap()
+            .canonicalize_utf8()
+            .unwrap();
+        let project_path = test_dir_path.join("project");
+        fs::create_dir_all(project_path.join("src")).unwrap();
+        fs::write(project_path.join("src/main.rs"), "abc").unwrap();
+        let put_report = trash.put(&project_path).unwrap();
+        let (entry, file_path) = trash
+            .locate(project_path.join("src/main.rs"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.identifier, put_report.identifier);
+        assert_eq!(fs::read_to_string(file_path).unwrap(), "abc");
+        let (_, file_path) = trash.locate(&project_path).unwrap().unwrap();
+        assert!(file_path.is_dir());
+        assert!(
+            trash
+                .locate(project_path.join("src/lib.rs"))
+                .unwrap()
+                .is_none()
+        );
+        assert!(trash.locate(&test_dir_path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_locate_parent_dir() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path())
+            .unwrap()
+            .canonicalize_utf8()
+            .unwrap();
+        let project_path = test_dir_path.join("project");
+        fs::create_dir_all(&project_path).unwrap();
+        let put_report = trash.put(&project_path).unwrap();
+        let entry = trash.entry(&put_report.identifier).unwrap();
+        assert!(
+            entry
+                .file_path_of(project_path.join("../../etc/passwd"))
+                .is_none()
+        );
+        // NOTE: This path would lead to the trashinfo file of the entry
+        let path = project_path.join(format!("../../info/{}.trashinfo", put_report.identifier));
+        assert!(entry.file_path_of(&path).is_none());
+        assert!(trash.locate(&path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_extract() {
+        let trash = new_test_trash();
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path())
+            .unwrap()
+            .canonicalize_utf8()
+            .unwrap();
+        let project_path = test_dir_path.join("project");
+        fs::create_dir_all(project_path.join("src")).unwrap();
+        fs::write(project_path.join("src/main.rs"), "abc").unwrap();
+        fs::write(project_path.join("src/lib.rs"), "defgh").unwrap();
+        let put_report = trash.put(&project_path).unwrap();
+        let main_path = project_path.join("src/main.rs");
+        let restore_report = trash.extract(&put_report.identifier, &main_path).unwrap();
+        assert_eq!(restore_report.path, main_path);
+        assert_eq!(fs::read_to_string(&main_path).unwrap(), "abc");
+        assert!(!project_path.join("src/lib.rs").exists());
+        // NOTE: A new trash is needed, as the cached directory sizes are not updated
+        let trash = Trash::new(&trash.base_dir);
+        let entry = trash.entry(&put_report.identifier).unwrap();
+        assert_eq!(entry.size(), 5);
+        assert!(trash.locate(&main_path).unwrap().is_none());
+        assert!(trash.extract(&put_report.identifier, &main_path).is_err());
+        assert!(
+            trash
+                .extract(&put_report.identifier, &project_path)
+                .is_err()
+        );
+        assert!(
+            trash
+                .extract(&put_report.identifier, project_path.join("src/../../etc"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_entries_broken() {
+        let trash = new_test_trash();
+        let test_file = NamedTempFile::new("test.txt").unwrap();
+        test_file.write_str("abc").unwrap();
+        let test_file_path = Utf8Path::from_path(test_file.path()).unwrap();
+        let put_report = trash.put(test_file_path).unwrap();
+        fs::remove_file(trash.files_dir.join(&put_report.identifier)).unwrap();
+        let entries = trash.entries().unwrap().collect::<Vec<_>>();
+        assert_eq!(entries.len(), 1);
+        let err = entries[0].as_ref().unwrap_err();
+        assert_eq!(err.identifier(), put_report.identifier);
+        assert_eq!(
+            err.trashinfo_path(),
+            trash.trashinfo_path(&put_report.identifier)
+        );
+        assert!(err.to_string().starts_with("broken entry test.txt"));
     }
 }
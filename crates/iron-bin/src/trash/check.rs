@@ -0,0 +1,400 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Trash consistency check.
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    os::unix::fs::MetadataExt,
+};
+
+use anyhow::{Context, Result, bail};
+use camino::{Utf8Path, Utf8PathBuf};
+use camino_ext::Utf8PathExt;
+use chrono::{DateTime, Local};
+
+use super::dir_sizes::{DirSize, DirSizes};
+use super::info::TrashInfo;
+use super::{EXT_TRASHINFO, Trash, disk_size};
+
+/// Directory used as the original directory of orphan files, whose origin is unknown.
+const UNKNOWN_ORIGIN_DIR: &str = "/unknown-origin";
+
+/// Suffix of the temporary files written in place of `.trashinfo` files.
+const TRASHINFO_TEMP_SUFFIX: &str = ".trashinfo.tmp";
+
+/// Problem found in a trash.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TrashProblem {
+    /// `.trashinfo` file with no matching file.
+    DanglingInfo { trashinfo_path: Utf8PathBuf },
+
+    /// File with no matching `.trashinfo` file.
+    OrphanFile { file_path: Utf8PathBuf },
+
+    /// `.trashinfo` file that cannot be read.
+    InvalidInfo {
+        trashinfo_path: Utf8PathBuf,
+        error: String,
+    },
+
+    /// Record of the `directorysizes` file with no matching directory, or outdated.
+    StaleDirSize { name: String },
+
+    /// Leftover temporary file.
+    TempFile { path: Utf8PathBuf },
+
+    /// File in the `info` directory that is neither a `.trashinfo` file nor a known temporary file.
+    UnexpectedFile { path: Utf8PathBuf },
+}
+
+impl TrashProblem {
+    /// Return the category of this problem, in plural form.
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::DanglingInfo { .. } => "dangling trashinfo files",
+            Self::OrphanFile { .. } => "orphan files",
+            Self::InvalidInfo { .. } => "invalid trashinfo files",
+            Self::StaleDirSize { .. } => "stale directory sizes",
+            Self::TempFile { .. } => "temporary files",
+            Self::UnexpectedFile { .. } => "unexpected files",
+        }
+    }
+
+    /// Return whether this problem can be repaired.
+    pub fn is_repairable(&self) -> bool {
+        !matches!(self, Self::InvalidInfo { .. } | Self::UnexpectedFile { .. })
+    }
+}
+
+impl Display for TrashProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DanglingInfo { trashinfo_path } => write!(f, "{trashinfo_path}"),
+            Self::OrphanFile { file_path } => write!(f, "{file_path}"),
+            Self::InvalidInfo {
+                trashinfo_path,
+                error,
+            } => write!(f, "{trashinfo_path}: {error}"),
+            Self::StaleDirSize { name } => write!(f, "{name}"),
+            Self::TempFile { path } | Self::UnexpectedFile { path } => write!(f, "{path}"),
+        }
+    }
+}
+
+impl Trash {
+    /// Check the consistency of this trash.
+    ///
+    /// Return the problems found, grouped by category.
+    pub fn check(&self) -> Result<Vec<TrashProblem>> {
+        let mut problems = Vec::new();
+        let mut temp_files = Vec::new();
+        // Check trashinfo files
+        let mut info_dir_paths = self
+            .info_dir
+            .read_dir_utf8_or_empty()?
+            .map(|dir_entry| Ok(dir_entry?.into_path()))
+            .collect::<io::Result<Vec<_>>>()
+            .with_context(|| format!("cannot read directory {}", self.info_dir))?;
+        info_dir_paths.sort();
+        let mut invalid_infos = Vec::new();
+        let mut unexpected_files = Vec::new();
+        for path in info_dir_paths {
+            if !path.is_file()
+                || path
+                    .extension()
+                    .is_none_or(|extension| extension != EXT_TRASHINFO)
+            {
+                // NOTE: The info directory should only contain trashinfo files,
+                // but only known temporary files can be safely removed
+                if path.is_file()
+                    && path
+                        .file_name()
+                        .is_some_and(|name| name.ends_with(TRASHINFO_TEMP_SUFFIX))
+                {
+                    temp_files.push(path);
+                } else {
+                    unexpected_files.push(path);
+                }
+                continue;
+            }
+            let identifier = path.file_stem().expect("trashinfo path has no file name");
+            if let Err(err) = self.read_trashinfo(identifier) {
+                // NOTE: The outermost context only repeats the trashinfo path
+                let error = err
+                    .chain()
+                    .skip(1)
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(": ");
+                invalid_infos.push(TrashProblem::InvalidInfo {
+                    trashinfo_path: path,
+                    error,
+                });
+            } else if self.files_dir.join(identifier).symlink_metadata().is_err() {
+                problems.push(TrashProblem::DanglingInfo {
+                    trashinfo_path: path,
+                });
+            }
+        }
+        // Check files
+        let mut file_paths = self
+            .files_dir
+            .read_dir_utf8_or_empty()?
+            .map(|dir_entry| Ok(dir_entry?.into_path()))
+            .collect::<io::Result<Vec<_>>>()
+            .with_context(|| format!("cannot read directory {}", self.files_dir))?;
+        file_paths.sort();
+        for file_path in file_paths {
+            let identifier = file_path.file_name().expect("file path has no file name");
+            if !self.trashinfo_path(identifier).exists() {
+                problems.push(TrashProblem::OrphanFile { file_path });
+            }
+        }
+        problems.extend(invalid_infos);
+        // Check directory sizes
+        if self.directorysizes_file.exists() {
+            let dir_sizes = self.load_dir_sizes().with_context(|| {
+                format!(
+                    "cannot read directorysizes file {}",
+                    self.directorysizes_file
+                )
+            })?;
+            let mut names = dir_sizes
+                .values()
+                .filter(|dir_size| !self.is_dir_size_valid(dir_size))
+                .map(|dir_size| dir_size.name.clone())
+                .collect::<Vec<_>>();
+            names.sort();
+            problems.extend(
+                names
+                    .into_iter()
+                    .map(|name| TrashProblem::StaleDirSize { name }),
+            );
+        }
+        // Check temporary files
+        let mut base_dir_paths = self
+            .base_dir
+            .read_dir_utf8_or_empty()?
+            .map(|dir_entry| Ok(dir_entry?.into_path()))
+            .collect::<io::Result<Vec<_>>>()
+            .with_context(|| format!("cannot read directory {}", self.base_dir))?;
+        base_dir_paths.sort();
+        temp_files.extend(base_dir_paths.into_iter().filter(|path| {
+            path.file_name()
+                .is_some_and(Trash::is_directorysizes_temp_file)
+        }));
+        problems.extend(
+            temp_files
+                .into_iter()
+                .map(|path| TrashProblem::TempFile { path }),
+        );
+        problems.extend(
+            unexpected_files
+                .into_iter()
+                .map(|path| TrashProblem::UnexpectedFile { path }),
+        );
+        Ok(problems)
+    }
+
+    /// Repair the given problem, found by [`check`](Self::check).
+    ///
+    /// - A dangling `.trashinfo` file is removed.
+    /// - A `.trashinfo` file is created for an orphan file, with an original path in `/unknown-origin`.
+    /// - The `directorysizes` file is rebuilt from scratch for a stale directory size.
+    /// - A temporary file is removed.
+    ///
+    /// An invalid `.trashinfo` file cannot be repaired, as its original path is unknown,
+    /// and neither can an unexpected file, as it may belong to another program.
+    ///
+    /// In dry-run mode, nothing is changed.
+    pub fn repair(&self, problem: &TrashProblem) -> Result<()> {
+        match problem {
+            TrashProblem::InvalidInfo { trashinfo_path, .. } => {
+                bail!("cannot repair invalid trashinfo file {trashinfo_path}");
+            }
+            TrashProblem::UnexpectedFile { path } => {
+                bail!("cannot repair unexpected file {path}");
+            }
+            _ if self.dry_run => {
+                // Nothing to do
+            }
+            TrashProblem::DanglingInfo { trashinfo_path } => {
+                fs::remove_file(trashinfo_path)
+                    .with_context(|| format!("cannot remove trashinfo file {trashinfo_path}"))?;
+            }
+            TrashProblem::OrphanFile { file_path } => {
+                let identifier = file_path.file_name().expect("file path has no file name");
+                let metadata = file_path
+                    .symlink_metadata()
+                    .with_context(|| format!("cannot get metadata of file {file_path}"))?;
+                // NOTE: The deletion time is unknown, so the modification time is used instead
+                let deletion_time = metadata
+                    .modified()
+                    .map(|mtime| DateTime::<Local>::from(mtime).naive_local())
+                    .unwrap_or_else(|_| Local::now().naive_local());
+                let trashinfo = TrashInfo {
+                    path: Utf8Path::new(UNKNOWN_ORIGIN_DIR).join(identifier),
+                    deletion_time,
+                    operation: None,
+                };
+                let trashinfo_path = self.trashinfo_path(identifier);
+                let mut trashinfo_file = fs::OpenOptions::new()
+                    .create_new(true)
+                    .write(true)
+                    .open(&trashinfo_path)
+                    .with_context(|| format!("cannot create trashinfo file {trashinfo_path}"))?;
+                trashinfo.write_to(&mut trashinfo_file)?;
+            }
+            TrashProblem::StaleDirSize { .. } => {
+                self.rebuild_dir_sizes()?;
+            }
+            TrashProblem::TempFile { path } => {
+                fs::remove_file(path).with_context(|| format!("cannot remove file {path}"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuild the `directorysizes` file from scratch, with the actual sizes of the directory entries.
+    pub fn rebuild_dir_sizes(&self) -> Result<()> {
+        let mut dir_sizes = DirSizes::new();
+        for entry in self.entries()? {
+            // NOTE: Entries in error are skipped
+            let Ok(entry) = entry else {
+                continue;
+            };
+            let file_path = entry.file_path();
+            if !file_path
+                .symlink_metadata()
+                .is_ok_and(|metadata| metadata.is_dir())
+            {
+                continue;
+            }
+            let size = disk_size(&file_path)
+                .with_context(|| format!("cannot compute size of {file_path}"))?;
+            let trashinfo_path = entry.trashinfo_path();
+            let mtime = trashinfo_path
+                .metadata()
+                .with_context(|| format!("cannot get metadata of trashinfo file {trashinfo_path}"))?
+                .mtime() as u64;
+            let dir_size = DirSize {
+                name: entry.identifier,
+                size,
+                mtime,
+            };
+            dir_sizes.insert(dir_size.name.clone(), dir_size);
+        }
+        self.write_dir_sizes(&dir_sizes)
+    }
+
+    /// Return whether the given directory size matches a directory entry of this trash.
+    fn is_dir_size_valid(&self, dir_size: &DirSize) -> bool {
+        let is_dir = self
+            .files_dir
+            .join(&dir_size.name)
+            .symlink_metadata()
+            .is_ok_and(|metadata| metadata.is_dir());
+        let trashinfo_mtime = self
+            .trashinfo_path(&dir_size.name)
+            .metadata()
+            .map(|metadata| metadata.mtime() as u64);
+        is_dir && trashinfo_mtime.is_ok_and(|mtime| mtime == dir_size.mtime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_check_and_repair() {
+        let base_dir = TempDir::new().unwrap();
+        let trash = Trash::new(Utf8Path::from_path(base_dir.path()).unwrap());
+        let test_dir = TempDir::new().unwrap();
+        let test_dir_path = Utf8Path::from_path(test_dir.path()).unwrap();
+        for name in ["dangling", "invalid"] {
+            fs::write(test_dir_path.join(name), "abc").unwrap();
+            trash.put(test_dir_path.join(name)).unwrap();
+        }
+        assert_eq!(trash.check().unwrap(), vec![]);
+        // Create problems
+        fs::remove_file(trash.files_dir.join("dangling")).unwrap();
+        fs::write(trash.files_dir.join("orphan"), "abc").unwrap();
+        fs::write(trash.trashinfo_path("invalid"), "garbage").unwrap();
+        fs::write(&trash.directorysizes_file, "3 0 stale\n").unwrap();
+        fs::write(trash.info_dir.join("leftover.trashinfo.tmp"), "").unwrap();
+        fs::write(trash.base_dir.join("directorysizes.abc123.tmp"), "").unwrap();
+        fs::write(trash.info_dir.join("unexpected"), "").unwrap();
+        let problems = trash.check().unwrap();
+        assert_eq!(problems.len(), 7);
+        assert_eq!(
+            problems[0],
+            TrashProblem::DanglingInfo {
+                trashinfo_path: trash.trashinfo_path("dangling")
+            }
+        );
+        assert_eq!(
+            problems[1],
+            TrashProblem::OrphanFile {
+                file_path: trash.files_dir.join("orphan")
+            }
+        );
+        let TrashProblem::InvalidInfo { error, .. } = &problems[2] else {
+            panic!("unexpected problem: {:?}", problems[2]);
+        };
+        assert!(!error.is_empty());
+        assert!(!error.contains(trash.trashinfo_path("invalid").as_str()));
+        assert_eq!(
+            problems[3],
+            TrashProblem::StaleDirSize {
+                name: String::from("stale")
+            }
+        );
+        assert_eq!(
+            problems[4],
+            TrashProblem::TempFile {
+                path: trash.info_dir.join("leftover.trashinfo.tmp")
+            }
+        );
+        assert_eq!(
+            problems[5],
+            TrashProblem::TempFile {
+                path: trash.base_dir.join("directorysizes.abc123.tmp")
+            }
+        );
+        assert_eq!(
+            problems[6],
+            TrashProblem::UnexpectedFile {
+                path: trash.info_dir.join("unexpected")
+            }
+        );
+        // Repair problems
+        for problem in &problems {
+            assert_eq!(trash.repair(problem).is_ok(), problem.is_repairable());
+        }
+        assert_eq!(
+            trash.check().unwrap(),
+            vec![problems[2].clone(), problems[6].clone()]
+        );
+        let entry = trash.entry("orphan").unwrap();
+        assert_eq!(
+            entry.original_path(),
+            Utf8Path::new(UNKNOWN_ORIGIN_DIR).join("orphan")
+        );
+    }
+}
@@ -16,7 +16,7 @@
 
 use std::{
     collections::HashMap,
-    io::{BufRead, BufReader, Read},
+    io::{BufRead, BufReader, Read, Write},
 };
 
 use anyhow::{Context, Result, anyhow};
@@ -76,6 +76,13 @@ impl DirSize {
         let dir_size = DirSize { name, size, mtime };
         Ok(dir_size)
     }
+
+    fn write_to_line(&self, writer: &mut impl Write) -> Result<()> {
+        let DirSize { name, size, mtime } = self;
+        let name = urlencoding::encode(name);
+        writeln!(writer, "{size} {mtime} {name}")?;
+        Ok(())
+    }
 }
 
 pub(super) fn read_from(reader: &mut impl Read) -> Result<DirSizes> {
@@ -91,3 +98,54 @@ pub(super) fn read_from(reader: &mut impl Read) -> Result<DirSizes> {
     }
     Ok(dir_sizes)
 }
+
+pub(super) fn write_to(writer: &mut impl Write, dir_sizes: &DirSizes) -> Result<()> {
+    // NOTE: Records are sorted by name, so that the output is deterministic
+    let mut dir_sizes = dir_sizes.values().collect::<Vec<_>>();
+    dir_sizes.sort_by(|dir_size1, dir_size2| dir_size1.name.cmp(&dir_size2.name));
+    for dir_size in dir_sizes {
+        dir_size.write_to_line(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_from() {
+        let mut reader = "4096 1757362269 my%20dir\ninvalid\n16 1757362269000 other\n".as_bytes();
+        let dir_sizes = read_from(&mut reader).unwrap();
+        assert_eq!(dir_sizes.len(), 2);
+        assert_eq!(
+            dir_sizes["my dir"],
+            DirSize {
+                name: "my dir".into(),
+                size: 4096,
+                mtime: 1_757_362_269,
+            }
+        );
+        assert_eq!(dir_sizes["other"].mtime, 1_757_362_269);
+    }
+
+    #[test]
+    fn test_write_to() {
+        let mut dir_sizes = DirSizes::new();
+        for (name, size) in [("my dir", 4096), ("abc", 16)] {
+            let dir_size = DirSize {
+                name: name.into(),
+                size,
+                mtime: 1_757_362_269,
+            };
+            dir_sizes.insert(name.into(), dir_size);
+        }
+        let mut writer = Vec::new();
+        write_to(&mut writer, &dir_sizes).unwrap();
+        assert_eq!(
+            str::from_utf8(&writer).unwrap(),
+            "16 1757362269 abc\n4096 1757362269 my%20dir\n"
+        );
+        assert_eq!(read_from(&mut writer.as_slice()).unwrap(), dir_sizes);
+    }
+}
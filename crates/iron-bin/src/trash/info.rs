@@ -28,14 +28,21 @@ use ini::Ini;
 const SECTION_TRASH_INFO: &str = "Trash Info";
 const ENTRY_PATH: &str = "Path";
 const ENTRY_DELETION_DATE: &str = "DeletionDate";
+const ENTRY_OPERATION: &str = "X-IronBin-Operation";
 
 /// Trash info.
 ///
 /// Represents the contents of a `.trashinfo` file in the info directory of a trash.
+///
+/// # Extensions
+///
+/// The identifier of the operation that put the file in the trash, if any,
+/// is stored in the `X-IronBin-Operation` entry, which other implementations ignore.
 #[derive(Clone, Debug, PartialEq)]
 pub(super) struct TrashInfo {
     pub(super) path: Utf8PathBuf,
     pub(super) deletion_time: NaiveDateTime,
+    pub(super) operation: Option<String>,
 }
 
 impl TrashInfo {
@@ -78,10 +85,13 @@ impl TryFrom<&Ini> for TrashInfo {
         let deletion_date = deletion_date_entry
             .parse::<NaiveDateTime>()
             .with_context(|| format!("invalid deletion date: {deletion_date_entry}"))?;
+        // Entry: Operation (optional)
+        let operation = section.get(ENTRY_OPERATION).map(String::from);
         // Trash info
         let info = Self {
             path: path_entry.into(),
             deletion_time: deletion_date,
+            operation,
         };
         Ok(info)
     }
@@ -90,9 +100,9 @@ impl TryFrom<&Ini> for TrashInfo {
 impl From<&TrashInfo> for Ini {
     fn from(info: &TrashInfo) -> Ini {
         let mut ini = Ini::new();
-        ini
-            // Section: Trash Info
-            .with_section(Some(SECTION_TRASH_INFO))
+        // Section: Trash Info
+        let mut section = ini.with_section(Some(SECTION_TRASH_INFO));
+        section
             // Entry: Path
             .set(ENTRY_PATH, urlencoding::encode(info.path.as_str()))
             // Entry: Deletion date
@@ -100,6 +110,10 @@ impl From<&TrashInfo> for Ini {
                 ENTRY_DELETION_DATE,
                 info.deletion_time.format("%Y-%m-%dT%H:%M:%S").to_string(),
             );
+        // Entry: Operation (optional)
+        if let Some(operation) = &info.operation {
+            section.set(ENTRY_OPERATION, operation);
+        }
         ini
     }
 }
@@ -126,10 +140,23 @@ DeletionDate=2025-02-17T13:14:15
                     NaiveDate::from_ymd_opt(2025, 2, 17).unwrap(),
                     NaiveTime::from_hms_opt(13, 14, 15).unwrap(),
                 ),
+                operation: None,
             }
         );
     }
 
+    #[test]
+    fn test_read_from_with_operation() {
+        let mut trashinfo: &[u8] = b"\
+[Trash Info]
+Path=%2Fabc%2Fdef%2Fghi.xyz
+DeletionDate=2025-02-17T13:14:15
+X-IronBin-Operation=abc-123
+        ";
+        let trashinfo = TrashInfo::read_from(&mut trashinfo).unwrap();
+        assert_eq!(trashinfo.operation.as_deref(), Some("abc-123"));
+    }
+
     #[test]
     fn test_write_to() {
         let trashinfo = TrashInfo {
@@ -138,6 +165,29 @@ DeletionDate=2025-02-17T13:14:15
                 NaiveDate::from_ymd_opt(2025, 2, 17).unwrap(),
                 NaiveTime::from_hms_opt(13, 14, 15).unwrap(),
             ),
+            operation: None,
+        };
+        let mut bytes = Vec::<u8>::new();
+        trashinfo.write_to(&mut bytes).unwrap();
+        assert_eq!(
+            bytes,
+            b"\
+[Trash Info]
+Path=%2Fabc%2Fdef%2Fghi.xyz
+DeletionDate=2025-02-17T13:14:15
+"
+        );
+    }
+
+    #[test]
+    fn test_write_to_with_operation() {
+        let trashinfo = TrashInfo {
+            path: Utf8PathBuf::from("/abc/def/ghi.xyz"),
+            deletion_time: NaiveDateTime::new(
+                NaiveDate::from_ymd_opt(2025, 2, 17).unwrap(),
+                NaiveTime::from_hms_opt(13, 14, 15).unwrap(),
+            ),
+            operation: Some(String::from("abc-123")),
         };
         let mut bytes = Vec::<u8>::new();
         trashinfo.write_to(&mut bytes).unwrap();
@@ -147,6 +197,7 @@ DeletionDate=2025-02-17T13:14:15
 [Trash Info]
 Path=%2Fabc%2Fdef%2Fghi.xyz
 DeletionDate=2025-02-17T13:14:15
+X-IronBin-Operation=abc-123
 "
         );
     }
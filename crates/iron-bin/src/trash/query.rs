@@ -0,0 +1,264 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Trash query.
+
+use std::cell::OnceCell;
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::NaiveDateTime;
+use glob::Pattern;
+
+use super::{TrashEntry, TrashFileType};
+
+/// Query on the entries of a trash.
+///
+/// A query consists of filters that an entry must all satisfy to match the query.
+/// The default query has no filters and matches every entry.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TrashQuery {
+    under: Option<Utf8PathBuf>,
+    deleted_since: Option<NaiveDateTime>,
+    deleted_until: Option<NaiveDateTime>,
+    operation: Option<String>,
+    larger_than: Option<u64>,
+    smaller_than: Option<u64>,
+    file_type: Option<TrashFileType>,
+    name: Option<Pattern>,
+}
+
+impl TrashQuery {
+    /// Create a query that matches every entry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return this query restricted to the entries whose original path is inside the given directory.
+    ///
+    /// The directory must be absolute, like the original paths.
+    /// An entry whose original path is the directory itself doesn't match.
+    pub fn under(mut self, dir: impl Into<Utf8PathBuf>) -> Self {
+        self.under = Some(dir.into());
+        self
+    }
+
+    /// Return this query restricted to the entries deleted at or after the given time.
+    pub fn deleted_since(mut self, time: NaiveDateTime) -> Self {
+        self.deleted_since = Some(time);
+        self
+    }
+
+    /// Return this query restricted to the entries deleted at or before the given time.
+    pub fn deleted_until(mut self, time: NaiveDateTime) -> Self {
+        self.deleted_until = Some(time);
+        self
+    }
+
+    /// Return this query restricted to the entries put in the trash by the given operation.
+    pub fn operation(mut self, operation: impl Into<String>) -> Self {
+        self.operation = Some(operation.into());
+        self
+    }
+
+    /// Return this query restricted to the entries larger than the given size, in bytes.
+    ///
+    /// The size of a directory is its cached size, see [`TrashEntry::size`],
+    /// or its computed size if it is not cached, see [`TrashEntry::computed_size`].
+    pub fn larger_than(mut self, size: u64) -> Self {
+        self.larger_than = Some(size);
+        self
+    }
+
+    /// Return this query restricted to the entries smaller than the given size, in bytes.
+    ///
+    /// The size of a directory is its cached size, see [`TrashEntry::size`],
+    /// or its computed size if it is not cached, see [`TrashEntry::computed_size`].
+    pub fn smaller_than(mut self, size: u64) -> Self {
+        self.smaller_than = Some(size);
+        self
+    }
+
+    /// Return this query restricted to the entries of the given file type.
+    pub fn file_type(mut self, file_type: TrashFileType) -> Self {
+        self.file_type = Some(file_type);
+        self
+    }
+
+    /// Return this query restricted to the entries whose original file name matches the given glob pattern.
+    ///
+    /// Fail if the pattern is invalid.
+    pub fn name(mut self, pattern: &str) -> Result<Self> {
+        let pattern =
+            Pattern::new(pattern).with_context(|| format!("invalid glob pattern: {pattern}"))?;
+        self.name = Some(pattern);
+        Ok(self)
+    }
+
+    /// Return whether this query has no filters.
+    pub fn is_unfiltered(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Return whether the given entry matches this query.
+    pub fn matches(&self, entry: &TrashEntry) -> bool {
+        let size = OnceCell::new();
+        let size = || *size.get_or_init(|| entry_size(entry));
+        self.under
+            .as_ref()
+            .is_none_or(|dir| is_inside(entry.original_path(), dir))
+            && self
+                .deleted_since
+                .is_none_or(|time| *entry.deletion_time() >= time)
+            && self
+                .deleted_until
+                .is_none_or(|time| *entry.deletion_time() <= time)
+            && self
+                .operation
+                .as_ref()
+                .is_none_or(|operation| entry.operation() == Some(operation))
+            && self
+                .larger_than
+                .is_none_or(|larger_than| size() > larger_than)
+            && self
+                .smaller_than
+                .is_none_or(|smaller_than| size() < smaller_than)
+            && self
+                .file_type
+                .is_none_or(|file_type| entry.file_type() == file_type)
+            && self.name.as_ref().is_none_or(|pattern| {
+                entry
+                    .original_path()
+                    .file_name()
+                    .is_some_and(|name| pattern.matches(name))
+            })
+    }
+}
+
+/// Return the size of the given entry.
+///
+/// The size of a directory without a cached size is computed.
+fn entry_size(entry: &TrashEntry) -> u64 {
+    // NOTE: A directory without a cached size has a size of 0, like an empty directory
+    if entry.file_type() == TrashFileType::Dir && entry.size() == 0 {
+        entry.computed_size().unwrap_or_default()
+    } else {
+        entry.size()
+    }
+}
+
+/// Return whether the given path is inside the given directory.
+fn is_inside(path: &Utf8Path, dir: &Utf8Path) -> bool {
+    path != dir && path.starts_with(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn new_test_entry(original_path: impl Into<Utf8PathBuf>) -> TrashEntry {
+        new_test_entry_deleted_on(original_path, NaiveDateTime::default())
+    }
+
+    fn new_test_entry_deleted_on(
+        original_path: impl Into<Utf8PathBuf>,
+        deletion_time: NaiveDateTime,
+    ) -> TrashEntry {
+        new_test_entry_with(original_path, deletion_time, 0, TrashFileType::File)
+    }
+
+    fn new_test_entry_with(
+        original_path: impl Into<Utf8PathBuf>,
+        deletion_time: NaiveDateTime,
+        size: u64,
+        file_type: TrashFileType,
+    ) -> TrashEntry {
+        TrashEntry {
+            identifier: String::from("test"),
+            original_path: original_path.into(),
+            deletion_time,
+            size,
+            file_type,
+            trash_dir: Utf8PathBuf::from("/trash"),
+            operation: None,
+        }
+    }
+
+    #[test]
+    fn test_unfiltered() {
+        let query = TrashQuery::new();
+        assert!(query.is_unfiltered());
+        assert!(query.matches(&new_test_entry("/abc/def")));
+    }
+
+    #[test]
+    fn test_under() {
+        let query = TrashQuery::new().under("/abc");
+        assert!(!query.is_unfiltered());
+        assert!(query.matches(&new_test_entry("/abc/def")));
+        assert!(query.matches(&new_test_entry("/abc/def/ghi")));
+        assert!(!query.matches(&new_test_entry("/abc")));
+        assert!(!query.matches(&new_test_entry("/abcdef")));
+        assert!(!query.matches(&new_test_entry("/xyz/abc/def")));
+    }
+
+    #[test]
+    fn test_deleted_since_until() {
+        let time = |day| {
+            NaiveDate::from_ymd_opt(2025, 9, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+        };
+        let query = TrashQuery::new()
+            .deleted_since(time(8))
+            .deleted_until(time(10));
+        assert!(!query.matches(&new_test_entry_deleted_on("/abc", time(7))));
+        assert!(query.matches(&new_test_entry_deleted_on("/abc", time(8))));
+        assert!(query.matches(&new_test_entry_deleted_on("/abc", time(9))));
+        assert!(query.matches(&new_test_entry_deleted_on("/abc", time(10))));
+        assert!(!query.matches(&new_test_entry_deleted_on("/abc", time(11))));
+    }
+
+    #[test]
+    fn test_larger_smaller_than() {
+        let entry =
+            |size| new_test_entry_with("/abc", NaiveDateTime::default(), size, TrashFileType::File);
+        let query = TrashQuery::new().larger_than(10).smaller_than(20);
+        assert!(!query.matches(&entry(10)));
+        assert!(query.matches(&entry(11)));
+        assert!(query.matches(&entry(19)));
+        assert!(!query.matches(&entry(20)));
+    }
+
+    #[test]
+    fn test_file_type() {
+        let entry = |file_type| new_test_entry_with("/abc", NaiveDateTime::default(), 0, file_type);
+        let query = TrashQuery::new().file_type(TrashFileType::Dir);
+        assert!(query.matches(&entry(TrashFileType::Dir)));
+        assert!(!query.matches(&entry(TrashFileType::File)));
+        assert!(!query.matches(&entry(TrashFileType::Symlink)));
+    }
+
+    #[test]
+    fn test_name() {
+        assert!(TrashQuery::new().name("[").is_err());
+        let query = TrashQuery::new().name("*.rs").unwrap();
+        assert!(query.matches(&new_test_entry("/abc/main.rs")));
+        assert!(!query.matches(&new_test_entry("/abc/main.rs/def")));
+        assert!(!query.matches(&new_test_entry("/abc.rs/def")));
+    }
+}
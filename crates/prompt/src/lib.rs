@@ -12,22 +12,350 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Simple y/n prompt.
+//! Simple prompts.
+//!
+//! Questions are either y/n questions ([`Prompter::confirm`])
+//! or multiple-choice questions with single-key answers ([`Prompter::choose`]).
+//!
+//! A [`Prompter`] reads the answers from any input and writes the questions to any output,
+//! so that prompts can be tested.
 
-use std::io;
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 
-/// Prompt the user for a y/n answer to a question.
+/// Path of the controlling terminal.
+const TTY_PATH: &str = "/dev/tty";
+
+/// Key of the answer that prints the help of a multiple-choice question.
+const HELP_KEY: char = '?';
+
+/// Prompt the user for a y/n answer to a question, on the terminal.
 ///
 /// Any answer that starts with 'y' or 'Y' is treated as a yes;
 /// anything else (including an empty answer) is treated as a no.
+///
+/// See [`Prompter::tty`] and [`Prompter::confirm`].
 pub fn prompt(question: impl AsRef<str>) -> Result<bool> {
-    let question = question.as_ref();
-    eprint!("{question} [y/N] ");
-    let mut answer = String::with_capacity(10);
-    io::stdin()
-        .read_line(&mut answer)
-        .context("cannot prompt")?;
-    Ok(answer.to_lowercase().starts_with('y'))
+    Prompter::tty().confirm(question)
+}
+
+/// Choice of a multiple-choice question.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Choice {
+    key: char,
+    label: String,
+    to_all: bool,
+}
+
+impl Choice {
+    /// Create a choice selected with the given key.
+    ///
+    /// The key is case-insensitive.
+    pub fn new(key: char, label: impl Into<String>) -> Self {
+        Self {
+            key: key.to_ascii_lowercase(),
+            label: label.into(),
+            to_all: false,
+        }
+    }
+
+    /// Set whether this choice applies to all the remaining questions.
+    ///
+    /// Once such a choice is selected, it is the answer to every following question
+    /// asked with the same [`Choices`], without prompting.
+    pub fn with_to_all(mut self, to_all: bool) -> Self {
+        self.to_all = to_all;
+        self
+    }
+
+    pub fn key(&self) -> char {
+        self.key
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn is_to_all(&self) -> bool {
+        self.to_all
+    }
+}
+
+/// Choices of a multiple-choice question, which may be asked several times.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Choices {
+    choices: Vec<Choice>,
+    default: Option<char>,
+    /// Key of the choice that applies to all the remaining questions, once selected.
+    to_all: Option<char>,
+}
+
+impl Choices {
+    /// Create choices.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if two choices have the same key, or if a choice has the help key ('?').
+    pub fn new(choices: impl IntoIterator<Item = Choice>) -> Self {
+        let choices = choices.into_iter().collect::<Vec<_>>();
+        for (index, choice) in choices.iter().enumerate() {
+            assert_ne!(choice.key, HELP_KEY, "the help key is reserved");
+            assert!(
+                choices[..index].iter().all(|other| other.key != choice.key),
+                "duplicate key {}",
+                choice.key
+            );
+        }
+        Self {
+            choices,
+            default: None,
+            to_all: None,
+        }
+    }
+
+    /// Set the key of the default choice, selected by an empty answer or the end of the input.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if no choice has the given key.
+    pub fn with_default(mut self, key: char) -> Self {
+        let key = key.to_ascii_lowercase();
+        assert!(self.choice(key).is_some(), "no choice with key {key}");
+        self.default = Some(key);
+        self
+    }
+
+    /// Return the choice with the given key, if any.
+    fn choice(&self, key: char) -> Option<&Choice> {
+        let key = key.to_ascii_lowercase();
+        self.choices.iter().find(|choice| choice.key == key)
+    }
+
+    /// Return the choice corresponding to an answer, if any.
+    ///
+    /// The answer is either the key or the label of a choice, case-insensitively.
+    fn parse(&self, answer: &str) -> Option<&Choice> {
+        let mut chars = answer.chars();
+        match (chars.next(), chars.next()) {
+            (Some(key), None) => self.choice(key),
+            _ => self
+                .choices
+                .iter()
+                .find(|choice| choice.label.eq_ignore_ascii_case(answer)),
+        }
+    }
+
+    /// Return the hint listing the keys, as in `[o,r,S,q,?]`.
+    ///
+    /// The key of the default choice is in uppercase.
+    fn hint(&self) -> String {
+        let keys = self
+            .choices
+            .iter()
+            .map(|choice| {
+                if Some(choice.key) == self.default {
+                    choice.key.to_ascii_uppercase()
+                } else {
+                    choice.key
+                }
+            })
+            .chain([HELP_KEY])
+            .map(String::from)
+            .collect::<Vec<_>>();
+        format!("[{}]", keys.join(","))
+    }
+}
+
+/// Prompter, which asks questions on an output and reads the answers from an input.
+pub struct Prompter<R = Box<dyn BufRead>, W = Box<dyn Write>> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    /// Create a prompter that reads from the given input and writes to the given output.
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    /// Read a line from the input, without the line terminator.
+    ///
+    /// Return `None` at the end of the input.
+    fn read_answer(&mut self) -> Result<Option<String>> {
+        let mut answer = String::with_capacity(10);
+        let count = self.input.read_line(&mut answer).context("cannot prompt")?;
+        if count == 0 {
+            // NOTE: Terminate the line of the question, as the user did not
+            writeln!(self.output).context("cannot prompt")?;
+            return Ok(None);
+        }
+        Ok(Some(answer.trim().to_string()))
+    }
+
+    /// Ask a y/n question.
+    ///
+    /// Any answer that starts with 'y' or 'Y' is treated as a yes;
+    /// anything else (including an empty answer or the end of the input) is treated as a no.
+    pub fn confirm(&mut self, question: impl AsRef<str>) -> Result<bool> {
+        let question = question.as_ref();
+        write!(self.output, "{question} [y/N] ").context("cannot prompt")?;
+        self.output.flush().context("cannot prompt")?;
+        let answer = self.read_answer()?.unwrap_or_default();
+        Ok(answer.to_lowercase().starts_with('y'))
+    }
+
+    /// Ask a multiple-choice question, and return the key of the selected choice.
+    ///
+    /// The answer is either the key or the label of a choice, case-insensitively.
+    /// An empty answer or the end of the input selects the default choice, if any.
+    /// The question is asked again until a choice is selected; the answer '?' prints the choices.
+    ///
+    /// If a choice that applies to all the remaining questions was selected earlier,
+    /// it is returned without prompting.
+    pub fn choose(&mut self, question: impl AsRef<str>, choices: &mut Choices) -> Result<char> {
+        if let Some(key) = choices.to_all {
+            return Ok(key);
+        }
+        let question = question.as_ref();
+        let hint = choices.hint();
+        loop {
+            write!(self.output, "{question} {hint} ").context("cannot prompt")?;
+            self.output.flush().context("cannot prompt")?;
+            let choice = match self.read_answer()? {
+                Some(answer) if answer == HELP_KEY.to_string() => {
+                    for choice in &choices.choices {
+                        writeln!(self.output, "{} - {}", choice.key, choice.label)
+                            .context("cannot prompt")?;
+                    }
+                    writeln!(self.output, "{HELP_KEY} - print help").context("cannot prompt")?;
+                    continue;
+                }
+                Some(answer) if !answer.is_empty() => choices.parse(&answer),
+                answer => match choices.default {
+                    Some(key) => choices.choice(key),
+                    None if answer.is_none() => bail!("cannot prompt: no answer"),
+                    None => None,
+                },
+            };
+            if let Some(choice) = choice {
+                let key = choice.key;
+                if choice.to_all {
+                    choices.to_all = Some(key);
+                }
+                return Ok(key);
+            }
+        }
+    }
+}
+
+impl Prompter {
+    /// Create a prompter that reads from the standard input and writes to the standard error.
+    pub fn stdio() -> Self {
+        Self::new(Box::new(io::stdin().lock()), Box::new(io::stderr()))
+    }
+
+    /// Create a prompter on the controlling terminal.
+    ///
+    /// This allows prompting even if the standard input is redirected, e.g. to read paths from it.
+    /// If there is no controlling terminal, the prompter is the same as [`Prompter::stdio`].
+    pub fn tty() -> Self {
+        let open_tty = || -> io::Result<(File, File)> {
+            let input = File::open(TTY_PATH)?;
+            let output = OpenOptions::new().write(true).open(TTY_PATH)?;
+            Ok((input, output))
+        };
+        match open_tty() {
+            Ok((input, output)) => Self::new(Box::new(BufReader::new(input)), Box::new(output)),
+            Err(_) => Self::stdio(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_prompter(input: &str) -> Prompter<&[u8], Vec<u8>> {
+        Prompter::new(input.as_bytes(), Vec::new())
+    }
+
+    fn output<'a>(prompter: &'a Prompter<&[u8], Vec<u8>>) -> &'a str {
+        str::from_utf8(prompter.output()).unwrap()
+    }
+
+    fn test_choices() -> Choices {
+        Choices::new([
+            Choice::new('o', "overwrite"),
+            Choice::new('r', "rename"),
+            Choice::new('s', "skip"),
+            Choice::new('a', "all").with_to_all(true),
+            Choice::new('q', "quit"),
+        ])
+        .with_default('s')
+    }
+
+    #[test]
+    fn test_confirm() {
+        let mut prompter = test_prompter("y\nYes\nn\n\nabc\n");
+        assert!(prompter.confirm("a?").unwrap());
+        assert!(prompter.confirm("b?").unwrap());
+        assert!(!prompter.confirm("c?").unwrap());
+        assert!(!prompter.confirm("d?").unwrap());
+        assert!(!prompter.confirm("e?").unwrap());
+        assert!(!prompter.confirm("f?").unwrap());
+        assert_eq!(
+            output(&prompter),
+            "a? [y/N] b? [y/N] c? [y/N] d? [y/N] e? [y/N] f? [y/N] \n"
+        );
+    }
+
+    #[test]
+    fn test_choose() {
+        let mut prompter = test_prompter("o\nR\nSkip\n\nx\n?\nq\n");
+        let mut choices = test_choices();
+        assert_eq!(prompter.choose("a", &mut choices).unwrap(), 'o');
+        assert_eq!(prompter.choose("b", &mut choices).unwrap(), 'r');
+        assert_eq!(prompter.choose("c", &mut choices).unwrap(), 's');
+        assert_eq!(prompter.choose("d", &mut choices).unwrap(), 's');
+        assert_eq!(prompter.choose("e", &mut choices).unwrap(), 'q');
+        assert_eq!(
+            output(&prompter),
+            "a [o,r,S,a,q,?] b [o,r,S,a,q,?] c [o,r,S,a,q,?] d [o,r,S,a,q,?] \
+             e [o,r,S,a,q,?] e [o,r,S,a,q,?] \
+             o - overwrite\nr - rename\ns - skip\na - all\nq - quit\n? - print help\n\
+             e [o,r,S,a,q,?] "
+        );
+    }
+
+    #[test]
+    fn test_choose_to_all() {
+        let mut prompter = test_prompter("r\na\n");
+        let mut choices = test_choices();
+        assert_eq!(prompter.choose("a", &mut choices).unwrap(), 'r');
+        assert_eq!(prompter.choose("b", &mut choices).unwrap(), 'a');
+        assert_eq!(prompter.choose("c", &mut choices).unwrap(), 'a');
+        assert_eq!(output(&prompter), "a [o,r,S,a,q,?] b [o,r,S,a,q,?] ");
+    }
+
+    #[test]
+    fn test_choose_end_of_input() {
+        let mut prompter = test_prompter("");
+        assert_eq!(prompter.choose("a", &mut test_choices()).unwrap(), 's');
+        let mut choices = Choices::new([Choice::new('o', "overwrite")]);
+        assert!(prompter.choose("b", &mut choices).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate key o")]
+    fn test_choices_duplicate_key() {
+        Choices::new([Choice::new('o', "overwrite"), Choice::new('O', "other")]);
+    }
 }
@@ -16,55 +16,244 @@
 
 use std::{
     cmp::Ordering,
-    fmt::Display,
-    io::{IsTerminal, stdout},
+    collections::HashMap,
+    env,
+    ffi::OsStr,
+    fmt::{self, Display},
+    fs,
+    io::{self, IsTerminal, Read, Write, stdin, stdout},
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+    path::{Path, PathBuf},
 };
 
-use anyhow::{Context, Result, bail};
-use camino::Utf8Path;
-use chrono::NaiveDateTime;
+use anyhow::{Context, Result, anyhow, bail};
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{DateTime, Local, NaiveDateTime};
 use clap::Parser;
 use humansize::{DECIMAL, FormatSizeOptions, make_format};
-use iron_bin::{Trash, TrashEmptyReport, TrashEntry};
+use iron_bin::{
+    Trash, TrashEmptyReport, TrashEntry, TrashFileType, TrashProblem, TrashQuery, disk_size,
+};
+use lscolors::LsColors;
 use prompt::prompt;
 use shell_quote::Sh;
 use tabled::{
     Table, Tabled,
+    builder::Builder,
     settings::{Alignment, Style, object::Columns},
 };
 
-use crate::cli::{Cli, Command, EmptyArgs, ListArgs, PutArgs, RestoreArgs, SortOrder};
+use crate::cli::{
+    CatArgs, Cli, ColorMode, Command, DiffArgs, EmptyArgs, FileType, FsckArgs, InfoArgs, ListArgs,
+    ListColumn, ListFormat, PutArgs, RestoreArgs, RmArgs, SelectionArgs, SortOrder, StatsArgs,
+    TRASH_DIR_ENV, TreeArgs, UndoArgs, VersionsArgs,
+};
+use crate::color::paint_entry;
+use crate::diff;
+use crate::pattern::PathPattern;
+use crate::picker;
+use crate::time::format_time;
+
+/// Header of the CSV output of the `list` command.
+///
+/// It must match the fields of a serialized entry, which are only written along with the first entry.
+const CSV_HEADER: [&str; 7] = [
+    "identifier",
+    "original_path",
+    "deletion_time",
+    "size",
+    "file_type",
+    "trash_dir",
+    "operation",
+];
+
+/// Number of paths above which the `put` command prompts with the '-I' option.
+const PROMPT_ONCE_THRESHOLD: usize = 3;
 
 /// Application.
 #[derive(Clone, Debug)]
-pub struct App;
+pub struct App {
+    /// Trash on which the commands operate.
+    trash: Trash,
+}
 
-/// Table record for a trash entry.
+/// Table record for a version of a path.
 #[derive(Tabled)]
-struct Record {
+struct VersionRecord {
+    #[tabled(rename = "version")]
+    version: usize,
+
     #[tabled(rename = "size")]
     size: String,
 
     #[tabled(rename = "deletion time")]
     deletion_time: String,
 
-    #[tabled(rename = "original path")]
-    path: String,
+    #[tabled(rename = "identifier")]
+    identifier: String,
+}
+
+/// Error returned when the trash contains broken entries, but the command otherwise succeeded.
+#[derive(Debug)]
+pub struct BrokenEntries(pub usize);
+
+impl BrokenEntries {
+    /// Exit status of the command.
+    ///
+    /// It differs from the exit status of clap for usage errors, which is 2.
+    pub const EXIT_STATUS: u8 = 3;
+}
+
+impl Display for BrokenEntries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} broken entries", self.0)
+    }
+}
+
+impl std::error::Error for BrokenEntries {}
+
+/// Breakdown of entries into groups, with the count and total size of each group.
+struct Breakdown {
+    groups: HashMap<String, (usize, u64)>,
+}
+
+impl Breakdown {
+    fn new() -> Self {
+        Self {
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Add an entry of the given size to the given group.
+    fn add(&mut self, group: String, size: u64) {
+        let (count, total_size) = self.groups.entry(group).or_default();
+        *count += 1;
+        *total_size += size;
+    }
+
+    /// Return the groups sorted by size, descending, then by name.
+    fn sorted(self) -> Vec<(String, (usize, u64))> {
+        let mut groups = self.groups.into_iter().collect::<Vec<_>>();
+        groups.sort_by(|(group1, (_, size1)), (group2, (_, size2))| {
+            size2.cmp(size1).then_with(|| group1.cmp(group2))
+        });
+        groups
+    }
 }
 
 impl App {
     pub fn run() -> Result<()> {
         let cli = Cli::parse();
-        let app = App;
+        let app = App {
+            trash: trash_at(cli.trash_dir.as_deref())?,
+        };
         match &cli.command {
             Command::List(args) => app.list(args),
             Command::Put(args) => app.put(args),
             Command::Restore(args) => app.restore(args),
+            Command::Rm(args) => app.rm(args),
+            Command::Info(args) => app.info(args),
+            Command::Tree(args) => app.tree(args),
+            Command::Cat(args) => app.cat(args),
+            Command::Diff(args) => app.diff(args),
+            Command::Stats(args) => app.stats(args),
+            Command::Versions(args) => app.versions(args),
+            Command::Undo(args) => app.undo(args),
+            Command::Fsck(args) => app.fsck(args),
             Command::Empty(args) => app.empty(args),
         }
     }
 
     fn list(&self, args: &ListArgs) -> Result<()> {
+        let trash = self.trash.clone();
+        // Build query
+        let mut query = TrashQuery::new();
+        if let Some(under) = &args.under {
+            query = query.under(absolute_dir(under)?);
+        }
+        if let Some(older_than) = args.older_than {
+            query = query.deleted_until(older_than);
+        }
+        if let Some(newer_than) = args.newer_than {
+            query = query.deleted_since(newer_than);
+        }
+        if let Some(larger_than) = args.larger_than {
+            query = query.larger_than(larger_than);
+        }
+        if let Some(smaller_than) = args.smaller_than {
+            query = query.smaller_than(smaller_than);
+        }
+        if let Some(file_type) = args.file_type {
+            query = query.file_type(match file_type {
+                FileType::File => TrashFileType::File,
+                FileType::Dir => TrashFileType::Dir,
+                FileType::Symlink => TrashFileType::Symlink,
+            });
+        }
+        if let Some(name) = &args.name {
+            query = query.name(name)?;
+        }
+        // Report broken entries
+        let mut entries = Vec::new();
+        let mut broken_count = 0_usize;
+        for entry in trash.query(&query)? {
+            match entry {
+                Ok(entry) => entries.push(entry),
+                Err(err) => {
+                    let level = if args.strict { "error" } else { "warning" };
+                    eprintln!("{level}: {err}");
+                    broken_count += 1;
+                }
+            }
+        }
+        if args.strict && broken_count > 0 {
+            bail!("{broken_count} broken entries");
+        }
+        // Sort entries according to sort order
+        entries.sort_by(comparator(&args.sort_order));
+        if args.reverse {
+            entries.reverse();
+        }
+        // Print entries
+        match args.format {
+            ListFormat::Text if args.null => {
+                let mut stdout = stdout().lock();
+                for entry in &entries {
+                    write!(stdout, "{}\0", entry.original_path())?;
+                }
+            }
+            ListFormat::Text => self.print_text_entries(args, &entries)?,
+            ListFormat::Json => {
+                let mut stdout = stdout().lock();
+                serde_json::to_writer_pretty(&mut stdout, &entries)?;
+                writeln!(stdout)?;
+            }
+            ListFormat::Jsonl => {
+                let mut stdout = stdout().lock();
+                for entry in &entries {
+                    serde_json::to_writer(&mut stdout, entry)?;
+                    writeln!(stdout)?;
+                }
+            }
+            ListFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(stdout().lock());
+                for entry in &entries {
+                    writer.serialize(entry)?;
+                }
+                // NOTE: The header is only written with the first record
+                if entries.is_empty() {
+                    writer.write_record(CSV_HEADER)?;
+                }
+                writer.flush()?;
+            }
+        }
+        if broken_count > 0 {
+            return Err(BrokenEntries(broken_count).into());
+        }
+        Ok(())
+    }
+
+    fn print_text_entries(&self, args: &ListArgs, entries: &[TrashEntry]) -> Result<()> {
         fn not_quoted(path: impl AsRef<Utf8Path>) -> String {
             let path = path.as_ref();
             path.to_string()
@@ -78,52 +267,115 @@ impl App {
                 .to_string()
         }
 
-        let trash = Trash::default();
-        let entries = trash.entries()?;
-        // Discard entries in error
-        let mut entries = entries.filter_map(|entry| entry.ok()).collect::<Vec<_>>();
-        // Sort entries according to sort order
-        entries.sort_by(comparator(&args.sort_order));
-        // Print entries
         let should_quote = stdout().is_terminal();
-        let maybe_quoted = if should_quote { quoted } else { not_quoted };
+        let maybe_quoted = |path: &Utf8Path| {
+            if should_quote {
+                quoted(path)
+            } else {
+                not_quoted(path)
+            }
+        };
+        let should_color = match args.color {
+            ColorMode::Auto => stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        };
+        // NOTE: If LS_COLORS is not set, the default colors of `dircolors` are used
+        let ls_colors = should_color.then(|| LsColors::from_env().unwrap_or_default());
+        let format_path = |entry: &TrashEntry| {
+            let path = maybe_quoted(entry.original_path());
+            match &ls_colors {
+                Some(ls_colors) => paint_entry(ls_colors, entry, path),
+                None => path,
+            }
+        };
         if !args.verbose {
-            for entry in &entries {
-                println!("{}", maybe_quoted(entry.original_path()))
+            for entry in entries {
+                if args.show_id {
+                    print!("{} ", maybe_quoted(Utf8Path::new(entry.identifier())));
+                }
+                println!("{}", format_path(entry))
             }
         } else {
             // NOTE: We use the DECIMAL format but remove the space after the value to mimic the behavior of `ls -lh`
             let size_formatter =
                 make_format(FormatSizeOptions::from(DECIMAL).space_after_value(false));
-            let mut table = Table::new(entries.iter().map(|entry| Record {
-                size: if args.human_readable {
-                    size_formatter(entry.size())
-                } else {
-                    format!("{}", entry.size())
-                },
-                deletion_time: format_datetime(entry.deletion_time()).to_string(),
-                path: maybe_quoted(entry.original_path()),
-            }));
-            table
-                .with(Style::empty())
-                .modify(Columns::first(), Alignment::right());
-            // TODO: Hide column headers
+            let mut columns = args
+                .columns
+                .clone()
+                .unwrap_or_else(|| vec![ListColumn::Size, ListColumn::Time, ListColumn::Path]);
+            if args.show_id && !columns.contains(&ListColumn::Id) {
+                columns.insert(0, ListColumn::Id);
+            }
+            let now = Local::now().naive_local();
+            let mut builder = Builder::default();
+            if !args.no_header {
+                builder.push_record(columns.iter().map(|column| match column {
+                    ListColumn::Size => "size",
+                    ListColumn::Time => "deletion time",
+                    ListColumn::Path => "original path",
+                    ListColumn::Id => "identifier",
+                    ListColumn::Type => "type",
+                    ListColumn::Trash => "trash directory",
+                }));
+            }
+            for entry in entries {
+                builder.push_record(columns.iter().map(|column| match column {
+                    ListColumn::Size => {
+                        if args.human_readable {
+                            size_formatter(entry.size())
+                        } else {
+                            format!("{}", entry.size())
+                        }
+                    }
+                    ListColumn::Time => format_time(entry.deletion_time(), &args.time_style, &now),
+                    ListColumn::Path => format_path(entry),
+                    ListColumn::Id => maybe_quoted(Utf8Path::new(entry.identifier())),
+                    ListColumn::Type => entry.file_type().to_string(),
+                    ListColumn::Trash => maybe_quoted(entry.trash_dir()),
+                }));
+            }
+            let mut table = builder.build();
+            table.with(Style::empty());
+            for (index, column) in columns.iter().enumerate() {
+                if *column == ListColumn::Size {
+                    table.modify(Columns::one(index), Alignment::right());
+                }
+            }
             println!("total {}", entries.len());
-            println!("{table}");
+            // NOTE: An empty table would be printed as an empty line
+            if table.count_rows() > 0 {
+                println!("{table}");
+            }
         }
         Ok(())
     }
 
     fn put(&self, args: &PutArgs) -> Result<()> {
-        let trash = Trash::default();
         let PutArgs {
+            dry_run,
+            files_from,
+            null,
             paths,
             interactive,
+            interactive_once,
             verbose,
         } = args;
-        let should_prompt = *interactive && stdout().is_terminal();
+        let operation = Trash::new_operation();
+        let trash = self
+            .trash
+            .clone()
+            .with_dry_run(*dry_run)
+            .with_operation(Some(operation.clone()));
+        let verbose = *verbose || *dry_run;
+        let should_prompt = *interactive && !*dry_run && stdout().is_terminal();
+        let should_prompt_once = *interactive_once && !*dry_run && stdout().is_terminal();
         let mut trashed = 0_usize;
         let mut errors = 0_usize;
+        let mut paths = paths.clone();
+        if let Some(files_from) = files_from {
+            paths.extend(read_paths(files_from, *null)?);
+        }
         // Discard invalid UTF-8 paths
         let paths = paths
             .iter()
@@ -135,11 +387,14 @@ impl App {
                 })
             })
             .collect::<Vec<_>>();
+        if should_prompt_once && !prompt_once(&paths)? {
+            return Ok(());
+        }
         for path in paths {
             if !should_prompt || prompt(format!("trash {path}?"))? {
                 match trash.put(path) {
                     Ok(report) => {
-                        if *verbose {
+                        if verbose {
                             println!(
                                 "trashed {} on {}",
                                 report.path,
@@ -155,7 +410,8 @@ impl App {
                 }
             }
         }
-        if *verbose {
+        if verbose {
+            println!("operation {operation}");
             println!("total {trashed} trashed");
         }
         if errors > 0 {
@@ -165,66 +421,649 @@ impl App {
     }
 
     fn restore(&self, args: &RestoreArgs) -> Result<()> {
-        let trash = Trash::default();
         let RestoreArgs {
+            dry_run,
             interactive,
             verbose,
+            pick,
+            sort_order,
+            selection,
+        } = args;
+        let trash = self.trash.clone().with_dry_run(*dry_run);
+        let verbose = *verbose || *dry_run;
+        let mut errors = 0_usize;
+        // Select entries
+        let mut inner_paths = Vec::new();
+        let entries = if *pick {
+            if !stdout().is_terminal() {
+                bail!("cannot pick files: the output is not a terminal");
+            }
+            let entries = self.select_entries(&trash, selection, true, None, &mut errors)?;
+            let Some(entries) = picker::pick(entries, *sort_order)? else {
+                return Ok(());
+            };
+            entries
+        } else {
+            self.select_entries(
+                &trash,
+                selection,
+                false,
+                Some(&mut inner_paths),
+                &mut errors,
+            )?
+        };
+        // Restore entries
+        self.restore_entries(
+            &trash,
+            &entries,
+            &inner_paths,
+            *interactive,
+            verbose,
+            errors,
+        )
+    }
+
+    fn rm(&self, args: &RmArgs) -> Result<()> {
+        let RmArgs {
+            dry_run,
+            interactive,
+            verbose,
+            selection,
+        } = args;
+        let trash = self.trash.clone().with_dry_run(*dry_run);
+        let verbose = *verbose || *dry_run;
+        let should_prompt = *interactive && !*dry_run && stdout().is_terminal();
+        let mut removed = 0_usize;
+        let mut errors = 0_usize;
+        // Select entries
+        let entries = self.select_entries(&trash, selection, false, None, &mut errors)?;
+        // Remove entries
+        for entry in &entries {
+            let identifier = entry.identifier();
+            let original_path = entry.original_path();
+            let deletion_time_disp = format_datetime(entry.deletion_time());
+            if !should_prompt
+                || prompt(format!(
+                    "remove {original_path} trashed on {deletion_time_disp} permanently?"
+                ))?
+            {
+                match trash.remove(identifier) {
+                    Ok(report) => {
+                        if verbose {
+                            println!("removed {} trashed on {}", report.path, &deletion_time_disp);
+                        }
+                        removed += 1;
+                    }
+                    Err(err) => {
+                        eprintln!("cannot remove {original_path}: {err:#}");
+                        errors += 1;
+                    }
+                }
+            }
+        }
+        if verbose {
+            println!("total {removed} removed");
+        }
+        if errors > 0 {
+            bail!("{errors} not removed");
+        }
+        Ok(())
+    }
+
+    /// Select entries in the given trash.
+    ///
+    /// Problems that don't prevent the selection, e.g. paths not found in the trash,
+    /// are reported and some of them are counted as errors.
+    ///
+    /// Without paths, the most recent entry is selected, unless `default_to_all` is true,
+    /// in which case every entry matching the filters is selected.
+    ///
+    /// If `inner_paths` is given, paths not found in the trash are searched inside trashed directories,
+    /// and pushed into it along with the entry of their directory.
+    fn select_entries(
+        &self,
+        trash: &Trash,
+        args: &SelectionArgs,
+        default_to_all: bool,
+        mut inner_paths: Option<&mut Vec<(TrashEntry, Utf8PathBuf)>>,
+        errors: &mut usize,
+    ) -> Result<Vec<TrashEntry>> {
+        let SelectionArgs {
+            files_from,
+            null,
+            regex,
+            all_versions,
+            latest: _,
+            version,
+            ids,
+            under,
+            since,
+            between,
             paths,
         } = args;
-        // Make paths absolute
-        // NOTE: We cannot use PathBuf::canonicalize here, as the paths likely don't exist anymore
-        let current_dir = std::env::current_dir().context("cannot determine current directory")?;
-        let paths = paths
+        // NOTE: An empty list read from a file must not default to the most recent entry
+        let most_recent = paths.is_empty() && files_from.is_none() && !default_to_all;
+        // NOTE: Paths read from a file are matched literally, as they may come from another command
+        // (e.g. find) and contain special characters
+        let mut paths = paths
             .iter()
-            .map(|path| current_dir.join(path))
+            .map(|path| (path.to_owned(), false))
             .collect::<Vec<_>>();
-        let mut restored = 0_usize;
-        let mut errors = 0_usize;
+        if let Some(files_from) = files_from {
+            paths.extend(
+                read_paths(files_from, *null)?
+                    .into_iter()
+                    .map(|path| (path, true)),
+            );
+        }
         // Discard invalid UTF-8 paths
         let paths = paths
             .iter()
-            .filter_map(|path| {
-                Utf8Path::from_path(path).or_else(|| {
+            .filter_map(|(path, literal)| {
+                let Some(path) = Utf8Path::from_path(path) else {
                     eprintln!("invalid UTF-8 path: {}", path.display());
-                    errors += 1;
-                    None
-                })
+                    *errors += 1;
+                    return None;
+                };
+                Some((path, *literal))
             })
             .collect::<Vec<_>>();
-        // Get entries
-        let entries = trash.entries()?;
-        // Discard entries in error
-        let mut entries = entries.filter_map(|entry| entry.ok()).collect::<Vec<_>>();
-        // Sort entries by deletion time, descending
-        entries.sort_by(comparator(&SortOrder::Date));
+        // Compile patterns, with paths made absolute unless they are regular expressions
+        // NOTE: We cannot use PathBuf::canonicalize here, as the paths likely don't exist anymore
+        let current_dir = Utf8PathBuf::from_path_buf(
+            std::env::current_dir().context("cannot determine current directory")?,
+        )
+        .map_err(|dir| anyhow!("invalid UTF-8 current directory: {}", dir.display()))?;
+        let patterns = paths
+            .into_iter()
+            .map(|(path, literal)| {
+                let pattern = if literal {
+                    PathPattern::literal(current_dir.join(path))
+                } else if *regex {
+                    PathPattern::regex(path)?
+                } else {
+                    PathPattern::glob_in(&current_dir, path)?
+                };
+                let path = if *regex && !literal {
+                    path.to_owned()
+                } else {
+                    current_dir.join(path)
+                };
+                Ok((path, pattern))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // Build query
+        let mut query = TrashQuery::new();
+        if let Some(under) = under {
+            query = query.under(absolute_dir(under)?);
+        }
+        if let Some(since) = since {
+            query = query.deleted_since(*since);
+        }
+        if let Some([start, end]) = between.as_deref() {
+            // NOTE: The times may be given in any order
+            query = query
+                .deleted_since(*start.min(end))
+                .deleted_until(*start.max(end));
+        }
+        // Get versions
+        let versions = trash.versions(&query)?;
         // Determine entries to restore
-        let entries = if paths.is_empty() {
-            // No paths specified, take the most recent entry
-            if let Some(entry) = entries.first() {
-                vec![entry]
-            } else {
-                bail!("empty trash");
-            }
-        } else {
-            // Path(s) specified, take most recent entry for each path
-            paths
-                .into_iter()
-                .filter_map(|path| {
+        let entries = if !ids.is_empty() {
+            // Identifier(s) specified, take the entry for each identifier
+            let entries = versions.values().flatten().collect::<Vec<_>>();
+            ids.iter()
+                .filter_map(|id| {
                     entries
                         .iter()
-                        .find(|entry| entry.original_path() == path)
+                        .find(|entry| entry.identifier() == id)
+                        .map(|entry| (*entry).clone())
                         .or_else(|| {
-                            eprintln!("file {path} not found in trash");
+                            eprintln!("identifier {id} not found in trash");
+                            *errors += 1;
                             None
                         })
                 })
                 .collect()
+        } else if most_recent && query.is_unfiltered() {
+            // No paths specified, take the most recent entry
+            if let Some(entry) = versions
+                .values()
+                .filter_map(|entries| entries.first())
+                .max_by_key(|entry| entry.deletion_time())
+            {
+                vec![entry.clone()]
+            } else {
+                bail!("empty trash");
+            }
+        } else {
+            // Path(s) or filter(s) specified, take matching paths
+            let paths = if patterns.is_empty() {
+                // No paths specified, take all paths matching the filters
+                versions.keys().collect::<Vec<_>>()
+            } else {
+                // Path(s) specified, take matching paths for each path
+                let mut selected_paths = Vec::new();
+                for (path, pattern) in patterns {
+                    let mut found = false;
+                    for original_path in versions
+                        .keys()
+                        .filter(|original_path| pattern.matches(original_path))
+                    {
+                        found = true;
+                        if !selected_paths.contains(&original_path) {
+                            selected_paths.push(original_path);
+                        }
+                    }
+                    if !found
+                        && !pattern.is_regex()
+                        && let Some(inner_paths) = inner_paths.as_deref_mut()
+                        && let Some((entry, _)) = trash.locate(&path)?
+                        && entry.original_path() != path
+                        && query.matches(&entry)
+                    {
+                        // Path inside a trashed directory
+                        found = true;
+                        inner_paths.push((entry, path.to_owned()));
+                    }
+                    if !found {
+                        eprintln!("file {path} not found in trash");
+                    }
+                }
+                selected_paths
+            };
+            // Take the requested version(s) of each path
+            let mut entries = Vec::new();
+            for path in paths {
+                let path_versions = &versions[path];
+                if *all_versions {
+                    entries.extend(path_versions.iter().cloned());
+                } else {
+                    let version = version.unwrap_or(1);
+                    let index = usize::try_from(version - 1).unwrap_or(usize::MAX);
+                    if let Some(entry) = path_versions.get(index) {
+                        entries.push(entry.clone());
+                    } else {
+                        eprintln!("version {version} of file {path} not found in trash");
+                        *errors += 1;
+                    }
+                }
+            }
+            entries
+        };
+        Ok(entries)
+    }
+
+    fn info(&self, args: &InfoArgs) -> Result<()> {
+        let InfoArgs { id, path } = args;
+        let trash = self.trash.clone();
+        // Determine entry
+        let entry = if let Some(id) = id {
+            trash.entry(id)?
+        } else if let Some(path) = path {
+            let path = absolute_path(path)?;
+            let versions = trash.versions(&TrashQuery::new())?;
+            let Some(entry) = versions.get(&path).and_then(|entries| entries.first()) else {
+                bail!("file {path} not found in trash");
+            };
+            entry.clone()
+        } else {
+            unreachable!("neither identifier nor path specified");
         };
+        // Print entry
+        println!("identifier: {}", entry.identifier());
+        println!("trash directory: {}", entry.trash_dir());
+        println!("original path: {}", entry.original_path());
+        println!("deletion time: {}", format_datetime(entry.deletion_time()));
+        println!("file: {}", entry.file_path());
+        println!("type: {}", entry.file_type());
+        match entry.metadata() {
+            Ok(metadata) => {
+                println!(
+                    "permissions: {} ({:04o})",
+                    format_mode(&metadata),
+                    metadata.mode() & 0o7777
+                );
+                println!(
+                    "owner: {}:{}",
+                    user_name(metadata.uid()),
+                    group_name(metadata.gid())
+                );
+                if let Some(mtime) = DateTime::from_timestamp(metadata.mtime(), 0) {
+                    println!(
+                        "modification time: {}",
+                        format_datetime(&mtime.with_timezone(&Local).naive_local())
+                    );
+                }
+            }
+            Err(err) => eprintln!("cannot get metadata of file {}: {err}", entry.file_path()),
+        }
+        match entry.file_type() {
+            TrashFileType::Dir => println!("size (cached): {}", entry.size()),
+            _ => println!("size: {}", entry.size()),
+        }
+        match entry.computed_size() {
+            Ok(size) => println!("size (computed): {size}"),
+            Err(err) => eprintln!("cannot compute size of file {}: {err}", entry.file_path()),
+        }
+        println!("trashinfo file: {}", entry.trashinfo_path());
+        let trashinfo = fs::read_to_string(entry.trashinfo_path())
+            .with_context(|| format!("cannot read trashinfo file {}", entry.trashinfo_path()))?;
+        print!("{trashinfo}");
+        if !trashinfo.ends_with('\n') {
+            println!();
+        }
+        Ok(())
+    }
+
+    fn tree(&self, args: &TreeArgs) -> Result<()> {
+        /// Print the contents of the given directory recursively, with the given prefix.
+        fn print_dir(dir: &Utf8Path, prefix: &str) -> Result<()> {
+            let mut dir_entries = dir
+                .read_dir_utf8()
+                .with_context(|| format!("cannot read directory {dir}"))?
+                .collect::<io::Result<Vec<_>>>()
+                .with_context(|| format!("cannot read directory {dir}"))?;
+            dir_entries.sort_by(|entry1, entry2| entry1.file_name().cmp(entry2.file_name()));
+            let count = dir_entries.len();
+            for (index, dir_entry) in dir_entries.iter().enumerate() {
+                let is_last = index + 1 == count;
+                let (branch, indent) = if is_last {
+                    ("└── ", "    ")
+                } else {
+                    ("├── ", "│   ")
+                };
+                println!("{prefix}{branch}{}", dir_entry.file_name());
+                let path = dir_entry.path();
+                if path.symlink_metadata()?.is_dir() {
+                    print_dir(path, &format!("{prefix}{indent}"))?;
+                }
+            }
+            Ok(())
+        }
+
+        let TreeArgs { paths } = args;
+        let trash = self.trash.clone();
+        let mut errors = 0_usize;
+        for path in paths {
+            let path = absolute_path(path)?;
+            let Some((_, file_path)) = trash.locate(&path)? else {
+                eprintln!("file {path} not found in trash");
+                errors += 1;
+                continue;
+            };
+            println!("{path}");
+            if file_path.symlink_metadata()?.is_dir()
+                && let Err(err) = print_dir(&file_path, "")
+            {
+                eprintln!("cannot list {path}: {err:#}");
+                errors += 1;
+            }
+        }
+        if errors > 0 {
+            bail!("{errors} not listed");
+        }
+        Ok(())
+    }
+
+    fn cat(&self, args: &CatArgs) -> Result<()> {
+        let CatArgs { paths } = args;
+        let trash = self.trash.clone();
+        let mut stdout = stdout().lock();
+        let mut errors = 0_usize;
+        for path in paths {
+            let path = absolute_path(path)?;
+            let Some((_, file_path)) = trash.locate(&path)? else {
+                eprintln!("file {path} not found in trash");
+                errors += 1;
+                continue;
+            };
+            let file_type = file_path.symlink_metadata()?.file_type();
+            if file_type.is_dir() {
+                eprintln!("file {path} is a directory");
+                errors += 1;
+            } else if file_type.is_symlink() {
+                eprintln!("file {path} is a symbolic link");
+                errors += 1;
+            } else {
+                let mut file = fs::File::open(&file_path)
+                    .with_context(|| format!("cannot open file {file_path}"))?;
+                io::copy(&mut file, &mut stdout)
+                    .with_context(|| format!("cannot print file {path}"))?;
+            }
+        }
+        if errors > 0 {
+            bail!("{errors} not printed");
+        }
+        Ok(())
+    }
+
+    fn diff(&self, args: &DiffArgs) -> Result<()> {
+        let DiffArgs { id, version, path } = args;
+        let trash = self.trash.clone();
+        // Determine entry and files to compare
+        let (entry, file_path, path) = if let Some(id) = id {
+            let entry = trash.entry(id)?;
+            let file_path = entry.file_path();
+            let path = entry.original_path().to_owned();
+            (entry, file_path, path)
+        } else if let Some(path) = path {
+            let path = absolute_path(path)?;
+            if let Some(version) = version {
+                let versions = trash.versions(&TrashQuery::new())?;
+                let index = usize::try_from(version - 1).unwrap_or(usize::MAX);
+                let Some(entry) = versions.get(&path).and_then(|entries| entries.get(index)) else {
+                    bail!("version {version} of file {path} not found in trash");
+                };
+                (entry.clone(), entry.file_path(), path)
+            } else {
+                let Some((entry, file_path)) = trash.locate(&path)? else {
+                    bail!("file {path} not found in trash");
+                };
+                (entry, file_path, path)
+            }
+        } else {
+            unreachable!("neither identifier nor path specified");
+        };
+        if path.symlink_metadata().is_err() {
+            bail!("file {path} not found");
+        }
+        // Compare files
+        let deletion_time_disp = format_datetime(entry.deletion_time());
+        let mut stdout = stdout().lock();
+        diff::diff(
+            &file_path,
+            format!("{path} (trashed on {deletion_time_disp})"),
+            &path,
+            &path,
+            &mut stdout,
+        )
+    }
+
+    fn stats(&self, args: &StatsArgs) -> Result<()> {
+        /// Age buckets, with their maximum ages in days
+        const AGE_BUCKETS: [(&str, i64); 4] = [
+            ("less than 1 day", 1),
+            ("1 to 7 days", 7),
+            ("7 to 30 days", 30),
+            ("30 to 365 days", 365),
+        ];
+        const AGE_BUCKET_OLDEST: &str = "more than 365 days";
+
+        let StatsArgs { human_readable } = args;
+        let trash = self.trash.clone();
+        let entries = trash.entries()?;
+        // Discard entries in error
+        let entries = entries.filter_map(|entry| entry.ok()).collect::<Vec<_>>();
+        let home_dir = env::home_dir().and_then(|dir| Utf8PathBuf::from_path_buf(dir).ok());
+        let now = Local::now().naive_local();
+        let mut total_size = 0_u64;
+        let mut by_dir = Breakdown::new();
+        let mut by_extension = Breakdown::new();
+        let mut by_age = Breakdown::new();
+        for entry in &entries {
+            // NOTE: The cached size of a directory may be missing or outdated, so the actual size is computed
+            let size = entry.computed_size().unwrap_or_else(|_| entry.size());
+            total_size += size;
+            by_dir.add(
+                top_level_dir(entry.original_path(), home_dir.as_deref()),
+                size,
+            );
+            let extension = if entry.file_type() == TrashFileType::Dir {
+                String::from("(directory)")
+            } else if let Some(extension) = entry.original_path().extension() {
+                format!(".{extension}")
+            } else {
+                String::from("(none)")
+            };
+            by_extension.add(extension, size);
+            let age = (now - *entry.deletion_time()).num_days();
+            let age_bucket = AGE_BUCKETS
+                .iter()
+                .find(|(_, max_age)| age < *max_age)
+                .map_or(AGE_BUCKET_OLDEST, |(name, _)| name);
+            by_age.add(age_bucket.to_string(), size);
+        }
+        // Print summary
+        let size_formatter = make_format(FormatSizeOptions::from(DECIMAL).space_after_value(false));
+        let format_size = |size: u64| {
+            if *human_readable {
+                size_formatter(size)
+            } else {
+                format!("{size}")
+            }
+        };
+        println!("entries: {}", entries.len());
+        println!("size: {}", format_size(total_size));
+        if let Some(entry) = entries.iter().min_by_key(|entry| entry.deletion_time()) {
+            println!(
+                "oldest deletion: {}",
+                format_datetime(entry.deletion_time())
+            );
+        }
+        if let Some(entry) = entries.iter().max_by_key(|entry| entry.deletion_time()) {
+            println!(
+                "newest deletion: {}",
+                format_datetime(entry.deletion_time())
+            );
+        }
+        if entries.is_empty() {
+            return Ok(());
+        }
+        // Print breakdowns
+        let breakdowns = [
+            ("directory", by_dir),
+            ("extension", by_extension),
+            ("age", by_age),
+        ];
+        for (name, breakdown) in breakdowns {
+            let mut builder = Builder::default();
+            builder.push_record(["entries", "size", name]);
+            for (group, (count, size)) in breakdown.sorted() {
+                builder.push_record([count.to_string(), format_size(size), group]);
+            }
+            let mut table = builder.build();
+            table
+                .with(Style::empty())
+                .modify(Columns::new(..2), Alignment::right());
+            println!();
+            println!("by {name}:");
+            println!("{table}");
+        }
+        Ok(())
+    }
+
+    fn versions(&self, args: &VersionsArgs) -> Result<()> {
+        let VersionsArgs {
+            human_readable,
+            path,
+        } = args;
+        let trash = self.trash.clone();
+        let path = absolute_path(path)?;
+        // Get versions
+        let versions = trash.versions(&TrashQuery::new())?;
+        let Some(entries) = versions.get(&path) else {
+            bail!("file {path} not found in trash");
+        };
+        // Print versions
+        let size_formatter = make_format(FormatSizeOptions::from(DECIMAL).space_after_value(false));
+        let mut table =
+            Table::new(
+                entries
+                    .iter()
+                    .enumerate()
+                    .map(|(index, entry)| VersionRecord {
+                        version: index + 1,
+                        size: if *human_readable {
+                            size_formatter(entry.size())
+                        } else {
+                            format!("{}", entry.size())
+                        },
+                        deletion_time: format_datetime(entry.deletion_time()).to_string(),
+                        identifier: entry.identifier().to_string(),
+                    }),
+            );
+        table
+            .with(Style::empty())
+            .modify(Columns::new(..2), Alignment::right());
+        println!("total {}", entries.len());
+        println!("{table}");
+        Ok(())
+    }
+
+    fn undo(&self, args: &UndoArgs) -> Result<()> {
+        let UndoArgs {
+            dry_run,
+            interactive,
+            verbose,
+            operation,
+        } = args;
+        let trash = self.trash.clone().with_dry_run(*dry_run);
+        let verbose = *verbose || *dry_run;
+        // Determine operation
+        let operation = if let Some(operation) = operation {
+            operation.to_owned()
+        } else if let Some(operation) = trash.last_operation()? {
+            operation
+        } else {
+            bail!("no operation to undo");
+        };
+        // Get entries
+        let entries = trash.query(&TrashQuery::new().operation(&operation))?;
+        // Discard entries in error
+        let mut entries = entries.filter_map(|entry| entry.ok()).collect::<Vec<_>>();
+        if entries.is_empty() {
+            bail!("operation {operation} not found in trash");
+        }
+        // Sort entries by path
+        entries.sort_by(comparator(&SortOrder::Path));
         // Restore entries
-        let should_prompt = *interactive && stdout().is_terminal();
-        for entry in entries {
+        self.restore_entries(&trash, &entries, &[], *interactive, verbose, 0)
+    }
+
+    /// Restore the given entries from the given trash.
+    ///
+    /// An entry that cannot be restored, e.g. because its original path is taken, is reported
+    /// but doesn't prevent the other entries from being restored.
+    /// The errors that occurred before, if any, are added to the errors reported at the end.
+    fn restore_entries(
+        &self,
+        trash: &Trash,
+        entries: &[TrashEntry],
+        inner_paths: &[(TrashEntry, Utf8PathBuf)],
+        interactive: bool,
+        verbose: bool,
+        mut errors: usize,
+    ) -> Result<()> {
+        let mut restored = 0_usize;
+        let should_prompt = interactive && !trash.is_dry_run() && stdout().is_terminal();
+        let entries = entries
+            .iter()
+            .map(|entry| (entry, None))
+            .chain(inner_paths.iter().map(|(entry, path)| (entry, Some(path))));
+        for (entry, inner_path) in entries {
             let identifier = entry.identifier();
-            let original_path = entry.original_path();
+            let original_path = inner_path.map_or(entry.original_path(), |path| path);
             let deletion_time = entry.deletion_time();
             let deletion_time_disp = format_datetime(deletion_time);
             if !should_prompt
@@ -232,9 +1071,14 @@ impl App {
                     "restore {original_path} trashed on {deletion_time_disp}?"
                 ))?
             {
-                match trash.restore(identifier) {
+                let result = if let Some(inner_path) = inner_path {
+                    trash.extract(identifier, inner_path)
+                } else {
+                    trash.restore(identifier)
+                };
+                match result {
                     Ok(report) => {
-                        if *verbose {
+                        if verbose {
                             println!(
                                 "restored {} trashed on {}",
                                 report.path, &deletion_time_disp
@@ -243,13 +1087,13 @@ impl App {
                         restored += 1;
                     }
                     Err(err) => {
-                        eprintln!("cannot restore {}: {err:#}", entry.original_path());
+                        eprintln!("cannot restore {original_path}: {err:#}");
                         errors += 1;
                     }
                 }
             }
         }
-        if *verbose {
+        if verbose {
             println!("total {restored} restored");
         }
         if errors > 0 {
@@ -258,31 +1102,315 @@ impl App {
         Ok(())
     }
 
+    fn fsck(&self, args: &FsckArgs) -> Result<()> {
+        let FsckArgs { repair, dry_run } = args;
+        let trash = self.trash.clone().with_dry_run(*dry_run);
+        let problems = trash.check()?;
+        let mut repaired = 0_usize;
+        let mut errors = 0_usize;
+        // NOTE: Repairing a stale directory size rebuilds all of them, so this is done only once
+        let mut dir_sizes_rebuilt = false;
+        for category_problems in
+            problems.chunk_by(|problem1, problem2| problem1.category() == problem2.category())
+        {
+            println!(
+                "{}: {}",
+                category_problems[0].category(),
+                category_problems.len()
+            );
+            for problem in category_problems {
+                println!("  {problem}");
+                if !*repair {
+                    continue;
+                }
+                let is_dir_size = matches!(problem, TrashProblem::StaleDirSize { .. });
+                if is_dir_size && dir_sizes_rebuilt {
+                    repaired += 1;
+                    continue;
+                }
+                match trash.repair(problem) {
+                    Ok(()) => {
+                        dir_sizes_rebuilt |= is_dir_size;
+                        repaired += 1;
+                    }
+                    Err(err) => {
+                        eprintln!("cannot repair {problem}: {err:#}");
+                        errors += 1;
+                    }
+                }
+            }
+        }
+        if *repair {
+            println!("total {} problems, {repaired} repaired", problems.len());
+            if errors > 0 {
+                bail!("{errors} not repaired");
+            }
+        } else {
+            println!("total {} problems", problems.len());
+            if !problems.is_empty() {
+                bail!("{} problems found", problems.len());
+            }
+        }
+        Ok(())
+    }
+
     fn empty(&self, args: &EmptyArgs) -> Result<()> {
-        let trash = Trash::default();
-        let EmptyArgs { force, verbose } = args;
-        let should_prompt = !*force && stdout().is_terminal();
+        let EmptyArgs {
+            dry_run,
+            force,
+            verbose,
+        } = args;
+        let trash = self.trash.clone().with_dry_run(*dry_run);
+        let verbose = *verbose || *dry_run;
+        let should_prompt = !*force && !*dry_run && stdout().is_terminal();
         if !should_prompt || prompt("empty trash?")? {
-            let TrashEmptyReport {
-                entry_count,
-                size: _,
-            } = trash.empty()?;
-            if *verbose {
-                // TODO: Print the size
-                println!("total {entry_count} removed");
+            let TrashEmptyReport { entry_count, size } = trash.empty()?;
+            if verbose {
+                println!("total {entry_count} removed, {size} bytes");
             }
         }
         Ok(())
     }
 }
 
-fn comparator(sort_order: &SortOrder) -> fn(&TrashEntry, &TrashEntry) -> Ordering {
+pub(crate) fn comparator(sort_order: &SortOrder) -> fn(&TrashEntry, &TrashEntry) -> Ordering {
     match sort_order {
         SortOrder::Path => |entry1, entry2| entry1.original_path().cmp(entry2.original_path()),
         SortOrder::Date => |entry1, entry2| entry2.deletion_time().cmp(entry1.deletion_time()),
+        // NOTE: Ties are broken by path, so that the order is deterministic
+        SortOrder::Size => |entry1, entry2| {
+            entry2
+                .size()
+                .cmp(&entry1.size())
+                .then_with(|| entry1.original_path().cmp(entry2.original_path()))
+        },
+        SortOrder::Name => |entry1, entry2| {
+            entry1
+                .original_path()
+                .file_name()
+                .cmp(&entry2.original_path().file_name())
+                .then_with(|| entry1.original_path().cmp(entry2.original_path()))
+        },
+        SortOrder::Trash => |entry1, entry2| {
+            entry1
+                .trash_dir()
+                .cmp(entry2.trash_dir())
+                .then_with(|| entry1.original_path().cmp(entry2.original_path()))
+        },
+    }
+}
+
+/// Return the top-level directory of the given original path.
+///
+/// Inside the given home directory, this is the directory directly inside it, e.g. `~/Documents`.
+/// Elsewhere, this is the directory directly inside the root, e.g. `/tmp`.
+fn top_level_dir(path: &Utf8Path, home_dir: Option<&Utf8Path>) -> String {
+    if let Some(home_dir) = home_dir
+        && let Ok(relative_path) = path.strip_prefix(home_dir)
+    {
+        let mut components = relative_path.components();
+        return match (components.next(), components.next()) {
+            (Some(first), Some(_)) => format!("~/{first}"),
+            _ => String::from("~"),
+        };
+    }
+    let mut components = path.components();
+    match (components.next(), components.next(), components.next()) {
+        (Some(root), Some(first), Some(_)) => format!("{root}{first}"),
+        _ => String::from("/"),
+    }
+}
+
+/// Return the given path as an absolute path.
+///
+/// The path is not canonicalized, as it likely doesn't exist anymore.
+fn absolute_path(path: impl AsRef<Path>) -> Result<Utf8PathBuf> {
+    let path = path.as_ref();
+    let current_dir = std::env::current_dir().context("cannot determine current directory")?;
+    let path = current_dir.join(path);
+    Utf8PathBuf::from_path_buf(path)
+        .map_err(|path| anyhow!("invalid UTF-8 path: {}", path.display()))
+}
+
+/// Return the trash at the given base directory, or the home trash.
+pub(crate) fn trash_at(base_dir: Option<&Path>) -> Result<Trash> {
+    match base_dir {
+        Some(base_dir) => Ok(Trash::new(absolute_dir(base_dir)?)),
+        None => Ok(Trash::default()),
     }
 }
 
+/// Return the trash at the directory given by the environment variable [`TRASH_DIR_ENV`],
+/// or the home trash.
+///
+/// This is for the commands that cannot take the '--trash-dir' option.
+pub(crate) fn trash_from_env() -> Result<Trash> {
+    let base_dir = env::var_os(TRASH_DIR_ENV)
+        .filter(|base_dir| !base_dir.is_empty())
+        .map(PathBuf::from);
+    trash_at(base_dir.as_deref())
+}
+
+/// Return the given directory as an absolute path.
+///
+/// If the directory exists, it is canonicalized, like the original paths of the trashed files.
+fn absolute_dir(dir: impl AsRef<Path>) -> Result<Utf8PathBuf> {
+    let dir = dir.as_ref();
+    let current_dir = std::env::current_dir().context("cannot determine current directory")?;
+    let dir = current_dir.join(dir);
+    let dir = dir.canonicalize().unwrap_or(dir);
+    Utf8PathBuf::from_path_buf(dir).map_err(|dir| anyhow!("invalid UTF-8 path: {}", dir.display()))
+}
+
+/// Read paths from the given file, or from the standard input if the file is `-`.
+///
+/// The paths are separated by NUL characters if `null` is true, or by newlines otherwise.
+/// Empty paths are ignored.
+fn read_paths(file: impl AsRef<Path>, null: bool) -> Result<Vec<PathBuf>> {
+    let file = file.as_ref();
+    let mut bytes = Vec::new();
+    if file == Path::new("-") {
+        stdin()
+            .read_to_end(&mut bytes)
+            .context("cannot read paths from standard input")?;
+    } else {
+        bytes =
+            fs::read(file).with_context(|| format!("cannot read paths from {}", file.display()))?;
+    }
+    let separator = if null { b'\0' } else { b'\n' };
+    let paths = bytes
+        .split(|byte| *byte == separator)
+        .filter(|path| !path.is_empty())
+        .map(|path| PathBuf::from(OsStr::from_bytes(path)))
+        .collect();
+    Ok(paths)
+}
+
+/// Format the permissions of a file like `ls -l`, e.g. `drwxr-xr-x`.
+fn format_mode(metadata: &fs::Metadata) -> String {
+    let file_type = metadata.file_type();
+    let type_char = if file_type.is_dir() {
+        'd'
+    } else if file_type.is_symlink() {
+        'l'
+    } else {
+        '-'
+    };
+    let mode = metadata.mode();
+    let mut s = String::with_capacity(10);
+    s.push(type_char);
+    for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        s.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        s.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        s.push(match (bits & 0o1 != 0, mode & special != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    s
+}
+
+/// Return the name of the user with the given ID, or the ID itself if the user is unknown.
+fn user_name(uid: u32) -> String {
+    uzers::get_user_by_uid(uid)
+        .map(|user| user.name().to_string_lossy().into_owned())
+        .unwrap_or_else(|| uid.to_string())
+}
+
+/// Return the name of the group with the given ID, or the ID itself if the group is unknown.
+fn group_name(gid: u32) -> String {
+    uzers::get_group_by_gid(gid)
+        .map(|group| group.name().to_string_lossy().into_owned())
+        .unwrap_or_else(|| gid.to_string())
+}
+
+/// Prompt once before putting paths in the trash, like `rm -I`.
+///
+/// See [`prompt_once_question`].
+fn prompt_once(paths: &[&Utf8Path]) -> Result<bool> {
+    let mut has_dir = false;
+    let mut total_size = 0_u64;
+    for path in paths {
+        // NOTE: Paths that cannot be trashed are ignored here, they are reported when trashed
+        if let Ok(metadata) = path.symlink_metadata() {
+            has_dir |= metadata.is_dir();
+        }
+        if let Ok(size) = disk_size(path) {
+            total_size += size;
+        }
+    }
+    match prompt_once_question(paths.len(), has_dir, total_size) {
+        Some(question) => prompt(question),
+        None => Ok(true),
+    }
+}
+
+/// Return the question to ask before putting paths in the trash, if any.
+///
+/// The user is only prompted if there are more than [`PROMPT_ONCE_THRESHOLD`] paths or any directory.
+/// The question shows the number of paths and their total size.
+fn prompt_once_question(count: usize, has_dir: bool, total_size: u64) -> Option<String> {
+    if count <= PROMPT_ONCE_THRESHOLD && !has_dir {
+        return None;
+    }
+    let plural = if count == 1 { "" } else { "s" };
+    let size_formatter = make_format(FormatSizeOptions::from(DECIMAL).space_after_value(false));
+    let including = if has_dir {
+        ", including directories"
+    } else {
+        ""
+    };
+    Some(format!(
+        "trash {count} path{plural} ({}){including}?",
+        size_formatter(total_size)
+    ))
+}
+
 fn format_datetime(datetime: &NaiveDateTime) -> impl Display {
     datetime.format("%c")
 }
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{TempDir, prelude::*};
+
+    use super::*;
+
+    #[test]
+    fn test_csv_header() {
+        let trash_dir = TempDir::new().unwrap();
+        let trash = Trash::new(Utf8Path::from_path(trash_dir.path()).unwrap());
+        let test_dir = TempDir::new().unwrap();
+        let test_file = test_dir.child("test.txt");
+        test_file.write_str("abc").unwrap();
+        let report = trash
+            .put(Utf8Path::from_path(test_file.path()).unwrap())
+            .unwrap();
+        let entry = trash.entry(&report.identifier).unwrap();
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.serialize(&entry).unwrap();
+        let output = writer.into_inner().unwrap();
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        assert_eq!(reader.headers().unwrap(), CSV_HEADER.as_slice());
+    }
+
+    #[test]
+    fn test_prompt_once_question() {
+        assert_eq!(prompt_once_question(1, false, 1000), None);
+        assert_eq!(
+            prompt_once_question(PROMPT_ONCE_THRESHOLD, false, 1000),
+            None
+        );
+        assert_eq!(
+            prompt_once_question(PROMPT_ONCE_THRESHOLD + 1, false, 1000).as_deref(),
+            Some("trash 4 paths (1kB)?")
+        );
+        assert_eq!(
+            prompt_once_question(1, true, 2500).as_deref(),
+            Some("trash 1 path (2.50kB), including directories?")
+        );
+    }
+}
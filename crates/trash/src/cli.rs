@@ -14,14 +14,29 @@
 
 //! CLI.
 
-use std::path::PathBuf;
+use std::{fmt::Write, path::PathBuf, str::FromStr};
 
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use chrono::NaiveDateTime;
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use clap_complete::ArgValueCompleter;
+
+use crate::complete::complete_trashed_paths;
+use crate::size::parse_size;
+use crate::time::parse_time;
+
+/// Environment variable that overrides the base directory of the trash.
+pub const TRASH_DIR_ENV: &str = "IRON_BIN_TRASH_DIR";
 
 /// Perform various operations on the trash.
 #[derive(Clone, Debug, Parser, PartialEq)]
 #[command(name = "trash", version)]
 pub struct Cli {
+    /// Base directory of the trash, e.g. a mounted volume's '.Trash-1000'.
+    ///
+    /// Defaults to the home trash.
+    #[arg(env = TRASH_DIR_ENV, global = true, long, value_name = "DIR")]
+    pub trash_dir: Option<PathBuf>,
+
     /// Command.
     #[command(subcommand)]
     pub command: Command,
@@ -42,6 +57,42 @@ pub enum Command {
     #[command()]
     Restore(RestoreArgs),
 
+    /// Remove files from the trash permanently.
+    #[command()]
+    Rm(RmArgs),
+
+    /// Show detailed information about a file in the trash.
+    #[command()]
+    Info(InfoArgs),
+
+    /// List the contents of directories in the trash.
+    #[command()]
+    Tree(TreeArgs),
+
+    /// Print the contents of files in the trash.
+    #[command()]
+    Cat(CatArgs),
+
+    /// Compare a file in the trash with the current file at its original path.
+    #[command()]
+    Diff(DiffArgs),
+
+    /// Summarize the disk usage of the trash.
+    #[command(visible_alias = "du")]
+    Stats(StatsArgs),
+
+    /// List the versions of a file in the trash.
+    #[command()]
+    Versions(VersionsArgs),
+
+    /// Restore the files put in the trash by an operation.
+    #[command()]
+    Undo(UndoArgs),
+
+    /// Check the consistency of the trash, and optionally repair it.
+    #[command()]
+    Fsck(FsckArgs),
+
     /// Empty the trash.
     #[command()]
     Empty(EmptyArgs),
@@ -70,9 +121,205 @@ pub struct ListArgs {
         value_name = "ORDER"
     )]
     pub sort_order: SortOrder,
+
+    /// Reverse the sort order.
+    #[arg(long, short = 'r')]
+    pub reverse: bool,
+
+    /// List only the files whose original path is inside a directory.
+    #[arg(long, value_name = "DIR")]
+    pub under: Option<PathBuf>,
+
+    /// List only the files put in the trash before a time.
+    ///
+    /// The time is either absolute (e.g. '2025-09-08 22:11') or relative to now (e.g. '10m', '2h' or '1d').
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    pub older_than: Option<NaiveDateTime>,
+
+    /// List only the files put in the trash after a time.
+    ///
+    /// The time is either absolute (e.g. '2025-09-08 22:11') or relative to now (e.g. '10m', '2h' or '1d').
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    pub newer_than: Option<NaiveDateTime>,
+
+    /// List only the files larger than a size.
+    ///
+    /// The size is in bytes, with an optional unit (e.g. '500', '10M' or '1GiB').
+    /// The size of a directory is computed if it is not cached.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub larger_than: Option<u64>,
+
+    /// List only the files smaller than a size.
+    ///
+    /// The size is in bytes, with an optional unit (e.g. '500', '10M' or '1GiB').
+    /// The size of a directory is computed if it is not cached.
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub smaller_than: Option<u64>,
+
+    /// List only the files of a type.
+    #[arg(long = "type", value_name = "TYPE")]
+    pub file_type: Option<FileType>,
+
+    /// List only the files whose original name matches a glob pattern.
+    ///
+    /// Should be quoted to avoid shell expansion.
+    #[arg(long, value_name = "PATTERN")]
+    pub name: Option<String>,
+
+    /// Output format.
+    #[arg(default_value = "text", long, short = 'f', value_name = "FORMAT")]
+    pub format: ListFormat,
+
+    /// Print the identifiers of the files in the trash.
+    #[arg(conflicts_with_all = ["format", "null"], long)]
+    pub show_id: bool,
+
+    /// Columns to print, separated by commas.
+    ///
+    /// Useful with the '-v'/'--verbose' option.
+    #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+    pub columns: Option<Vec<ListColumn>>,
+
+    /// Do not print column headers.
+    ///
+    /// Useful with the '-v'/'--verbose' option.
+    #[arg(long)]
+    pub no_header: bool,
+
+    /// Time style: 'locale', 'iso', 'full-iso', 'relative' or '+FORMAT'.
+    ///
+    /// FORMAT is interpreted like in the `date` command.
+    /// Useful with the '-v'/'--verbose' option.
+    #[arg(default_value = "locale", long, value_name = "STYLE")]
+    pub time_style: TimeStyle,
+
+    /// Color paths according to the `LS_COLORS` environment variable: 'auto', 'always' or 'never'.
+    ///
+    /// With 'auto', paths are colored only if the output is a terminal
+    /// and the `NO_COLOR` environment variable is not set.
+    #[arg(
+        default_missing_value = "always",
+        default_value = "auto",
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        value_name = "WHEN"
+    )]
+    pub color: ColorMode,
+
+    /// Fail if the trash contains broken entries.
+    ///
+    /// By default, a warning is printed for each broken entry, and the exit status is 3.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Print paths terminated by NUL characters instead of newlines, without quoting.
+    #[arg(conflicts_with_all = ["verbose", "format"], long, short = '0')]
+    pub null: bool,
+}
+
+/// Output format for the `list` command.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum ListFormat {
+    /// Text, for humans.
+    #[default]
+    Text,
+
+    /// JSON array.
+    Json,
+
+    /// JSON Lines, one object per entry.
+    Jsonl,
+
+    /// CSV with a header.
+    Csv,
+}
+
+/// Color mode for the `list` command.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum ColorMode {
+    /// Color if the output is a terminal.
+    #[default]
+    Auto,
+
+    /// Always color.
+    Always,
+
+    /// Never color.
+    Never,
+}
+
+/// Column for the `list` command.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ListColumn {
+    /// Size.
+    Size,
+
+    /// Deletion time.
+    Time,
+
+    /// Original path.
+    Path,
+
+    /// Identifier.
+    Id,
+
+    /// File type.
+    Type,
+
+    /// Trash directory.
+    Trash,
+}
+
+/// Time style for the `list` command.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum TimeStyle {
+    /// Locale format, e.g. `Mon Sep  8 22:11:09 2025`.
+    #[default]
+    Locale,
+
+    /// ISO 8601 format, up to minutes, e.g. `2025-09-08 22:11`.
+    Iso,
+
+    /// ISO 8601 format, up to seconds, e.g. `2025-09-08T22:11:09`.
+    FullIso,
+
+    /// Relative to now, e.g. `3 days ago`.
+    Relative,
+
+    /// Custom format, as in the `date` command.
+    Format(String),
+}
+
+impl FromStr for TimeStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let time_style = match s {
+            "locale" => Self::Locale,
+            "iso" => Self::Iso,
+            "full-iso" => Self::FullIso,
+            "relative" => Self::Relative,
+            _ => {
+                let Some(format) = s.strip_prefix('+') else {
+                    return Err(format!(
+                        "invalid time style (expected 'locale', 'iso', 'full-iso', 'relative' or '+FORMAT'): {s}"
+                    ));
+                };
+                // NOTE: An invalid format, or one with a time zone (e.g. '%z'), would make chrono panic
+                // when formatting a naive time, so we format one to check it
+                let mut formatted = String::new();
+                if write!(formatted, "{}", NaiveDateTime::default().format(format)).is_err() {
+                    return Err(format!("invalid time format: {format}"));
+                }
+                Self::Format(format.to_string())
+            }
+        };
+        Ok(time_style)
+    }
 }
 
-/// Sort order for the `list` command.
+/// Sort order for the `list` command and the `restore --pick` command.
 #[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
 pub enum SortOrder {
     /// Path, ascending.
@@ -81,27 +328,125 @@ pub enum SortOrder {
 
     /// Deletion time, descending.
     Date,
+
+    /// Size, descending.
+    Size,
+
+    /// File name, ascending.
+    Name,
+
+    /// Trash directory, ascending, then path, ascending.
+    Trash,
+}
+
+/// File type for the `list` command.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum FileType {
+    /// Regular file.
+    File,
+
+    /// Directory.
+    Dir,
+
+    /// Symbolic link.
+    Symlink,
 }
 
 /// Arguments to the `put` command.
 #[derive(Args, Clone, Debug, PartialEq)]
 pub struct PutArgs {
+    /// Do not change anything, only print what would be done.
+    ///
+    /// Implies '-v'/'--verbose'.
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+
     /// Prompt before every path.
-    #[arg(long, short = 'i')]
+    #[arg(long, overrides_with = "interactive_once", short = 'i')]
     pub interactive: bool,
 
+    /// Prompt once before trashing more than three paths, or any directory.
+    ///
+    /// The prompt shows the number of paths and their total size.
+    #[arg(overrides_with = "interactive", short = 'I')]
+    pub interactive_once: bool,
+
     /// Verbose output.
     #[arg(long, short = 'v')]
     pub verbose: bool,
 
+    /// Read paths from a file, one per line.
+    ///
+    /// If FILE is '-', read paths from the standard input.
+    #[arg(long, value_name = "FILE")]
+    pub files_from: Option<PathBuf>,
+
+    /// Paths read with '--files-from' are terminated by NUL characters instead of newlines.
+    #[arg(long, requires = "files_from", short = '0')]
+    pub null: bool,
+
     /// Paths.
-    #[arg(required = true, value_name = "PATH")]
+    #[arg(required_unless_present = "files_from", value_name = "PATH")]
     pub paths: Vec<PathBuf>,
 }
 
 /// Arguments to the `restore` command.
 #[derive(Args, Clone, Debug, PartialEq)]
 pub struct RestoreArgs {
+    /// Do not change anything, only print what would be done.
+    ///
+    /// Implies '-v'/'--verbose'.
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+
+    /// Prompt before every path.
+    #[arg(long, short = 'i')]
+    pub interactive: bool,
+
+    /// Verbose output.
+    #[arg(long, short = 'v')]
+    pub verbose: bool,
+
+    /// Pick the files to restore in a full-screen terminal UI.
+    ///
+    /// The UI lists the files selected by the other options, or every file by default.
+    /// It supports fuzzy search, multiple selection and sorting, and shows a preview of the current file.
+    #[arg(conflicts_with_all = ["interactive", "ids"], long)]
+    pub pick: bool,
+
+    /// Initial sort order of the files listed by '--pick'.
+    #[arg(
+        default_value = "date",
+        long = "sort",
+        requires = "pick",
+        short = 's',
+        value_name = "ORDER"
+    )]
+    pub sort_order: SortOrder,
+
+    /// Selection of the files to restore.
+    #[command(flatten)]
+    pub selection: SelectionArgs,
+}
+
+/// Arguments to the `rm` command.
+///
+/// Unlike the `restore` command, at least one selector is required,
+/// so that the most recent file is never removed by default.
+#[derive(Args, Clone, Debug, PartialEq)]
+#[command(group(
+    ArgGroup::new("selector")
+        .args(["paths", "files_from", "ids", "under", "since", "between"])
+        .multiple(true)
+        .required(true)
+))]
+pub struct RmArgs {
+    /// Do not change anything, only print what would be done.
+    ///
+    /// Implies '-v'/'--verbose'.
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+
     /// Prompt before every path.
     #[arg(long, short = 'i')]
     pub interactive: bool,
@@ -110,18 +455,238 @@ pub struct RestoreArgs {
     #[arg(long, short = 'v')]
     pub verbose: bool,
 
+    /// Selection of the files to remove.
+    #[command(flatten)]
+    pub selection: SelectionArgs,
+}
+
+/// Arguments selecting files in the trash, for the `restore` and `rm` commands.
+#[derive(Args, Clone, Debug, PartialEq)]
+#[command(group(ArgGroup::new("path_sources").args(["paths", "files_from"]).multiple(true)))]
+pub struct SelectionArgs {
+    /// Read paths from a file, one per line.
+    ///
+    /// If FILE is '-', read paths from the standard input.
+    #[arg(long, value_name = "FILE")]
+    pub files_from: Option<PathBuf>,
+
+    /// Paths read with '--files-from' are terminated by NUL characters instead of newlines.
+    #[arg(long, requires = "files_from", short = '0')]
+    pub null: bool,
+
+    /// Interpret paths as regular expressions instead of glob patterns.
+    ///
+    /// Regular expressions must match the whole original path.
+    #[arg(long, short = 'E')]
+    pub regex: bool,
+
+    /// Select every version of the matching paths.
+    #[arg(conflicts_with = "version", long, overrides_with = "latest")]
+    pub all_versions: bool,
+
+    /// Select only the most recent version of the matching paths (default).
+    #[arg(conflicts_with = "version", long, overrides_with = "all_versions")]
+    pub latest: bool,
+
+    /// Select a specific version of the matching paths.
+    ///
+    /// Versions are numbered from 1, the most recent, as listed by the `versions` command.
+    /// Requires paths.
+    #[arg(
+        long,
+        requires = "path_sources",
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub version: Option<u64>,
+
+    /// Select the file with an identifier, as listed by the `versions` command.
+    ///
+    /// Can be repeated.
+    #[arg(
+        conflicts_with_all = ["paths", "files_from", "under", "since", "between", "version", "all_versions"],
+        long = "id",
+        value_name = "IDENTIFIER"
+    )]
+    pub ids: Vec<String>,
+
+    /// Select only the files whose original path is inside a directory.
+    ///
+    /// Without paths, select every such file.
+    #[arg(long, value_name = "DIR")]
+    pub under: Option<PathBuf>,
+
+    /// Select only the files trashed since a time.
+    ///
+    /// The time is either absolute (e.g. '2025-09-08 22:11') or relative to now (e.g. '10m', '2h' or '1d').
+    /// Without paths, select every such file.
+    #[arg(conflicts_with = "between", long, value_name = "TIME", value_parser = parse_time)]
+    pub since: Option<NaiveDateTime>,
+
+    /// Select only the files trashed between two times.
+    ///
+    /// The times are in the same format as for '--since'.
+    /// Without paths, select every such file.
+    #[arg(long, num_args = 2, value_names = ["START", "END"], value_parser = parse_time)]
+    pub between: Option<Vec<NaiveDateTime>>,
+
+    /// Paths.
+    ///
+    /// For the `restore` command, defaults to the most recently trashed file,
+    /// unless '--files-from', '--id' or a filter is specified.
+    ///
+    /// Paths are glob patterns matched against the original paths.
+    /// They should be quoted to avoid shell expansion.
+    #[arg(add = ArgValueCompleter::new(complete_trashed_paths), value_name = "PATH")]
+    pub paths: Vec<PathBuf>,
+}
+
+/// Arguments to the `info` command.
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct InfoArgs {
+    /// Select the file with an identifier, as listed by the `versions` command.
+    #[arg(conflicts_with = "path", long = "id", value_name = "IDENTIFIER")]
+    pub id: Option<String>,
+
+    /// Path.
+    ///
+    /// If the same file was put in the trash several times, the most recent version is selected.
+    ///
+    /// Should be quoted to avoid shell expansion.
+    #[arg(
+        add = ArgValueCompleter::new(complete_trashed_paths),
+        required_unless_present = "id",
+        value_name = "PATH"
+    )]
+    pub path: Option<PathBuf>,
+}
+
+/// Arguments to the `tree` command.
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct TreeArgs {
     /// Paths.
     ///
-    /// Defaults to the most recently trashed file.
+    /// Each path is either the original path of a directory in the trash,
+    /// or a path inside it.
+    /// If the same directory was put in the trash several times, the most recent version is selected.
     ///
     /// Should be quoted to avoid shell expansion.
-    #[arg(value_name = "PATH")]
+    #[arg(required = true, value_name = "PATH")]
+    pub paths: Vec<PathBuf>,
+}
+
+/// Arguments to the `cat` command.
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct CatArgs {
+    /// Paths.
+    ///
+    /// Each path is either the original path of a file in the trash,
+    /// or a path inside a directory in the trash.
+    /// If the same file was put in the trash several times, the most recent version is selected.
+    ///
+    /// Should be quoted to avoid shell expansion.
+    #[arg(required = true, value_name = "PATH")]
     pub paths: Vec<PathBuf>,
 }
 
+/// Arguments to the `diff` command.
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct DiffArgs {
+    /// Select the file with an identifier, as listed by the `versions` command.
+    #[arg(conflicts_with_all = ["path", "version"], long = "id", value_name = "IDENTIFIER")]
+    pub id: Option<String>,
+
+    /// Select a specific version of the file.
+    ///
+    /// Versions are numbered from 1, the most recent, as listed by the `versions` command.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub version: Option<u64>,
+
+    /// Path.
+    ///
+    /// Unless the `--version` option is specified, the path may also be inside a directory in the trash.
+    ///
+    /// Should be quoted to avoid shell expansion.
+    #[arg(required_unless_present = "id", value_name = "PATH")]
+    pub path: Option<PathBuf>,
+}
+
+/// Arguments to the `versions` command.
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct VersionsArgs {
+    /// Print human-readable sizes.
+    // NOTE: The short name `-h` is more conventional, but it conflicts with the help option
+    #[arg(long, short = 'H')]
+    pub human_readable: bool,
+
+    /// Path.
+    ///
+    /// Should be quoted to avoid shell expansion.
+    #[arg(value_name = "PATH")]
+    pub path: PathBuf,
+}
+
+/// Arguments to the `stats` command.
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct StatsArgs {
+    /// Print human-readable sizes.
+    // NOTE: The short name `-h` is more conventional, but it conflicts with the help option
+    #[arg(long, short = 'H')]
+    pub human_readable: bool,
+}
+
+/// Arguments to the `undo` command.
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct UndoArgs {
+    /// Do not change anything, only print what would be done.
+    ///
+    /// Implies '-v'/'--verbose'.
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+
+    /// Prompt before every path.
+    #[arg(long, short = 'i')]
+    pub interactive: bool,
+
+    /// Verbose output.
+    #[arg(long, short = 'v')]
+    pub verbose: bool,
+
+    /// Operation identifier.
+    ///
+    /// Defaults to the most recent operation.
+    ///
+    /// Each invocation of the `put` command is an operation.
+    #[arg(value_name = "OPERATION")]
+    pub operation: Option<String>,
+}
+
+/// Arguments to the `fsck` command.
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct FsckArgs {
+    /// Repair the problems found.
+    ///
+    /// Dangling trashinfo files and temporary files are removed,
+    /// trashinfo files are created for orphan files, with an original path in '/unknown-origin',
+    /// and the directory sizes are rebuilt.
+    /// Invalid trashinfo files and unexpected files are not repaired.
+    #[arg(long)]
+    pub repair: bool,
+
+    /// Do not change anything, only print what would be repaired.
+    #[arg(long, requires = "repair", short = 'n')]
+    pub dry_run: bool,
+}
+
 /// Arguments to the `empty` command.
 #[derive(Args, Clone, Debug, PartialEq)]
 pub struct EmptyArgs {
+    /// Do not change anything, only print what would be done.
+    ///
+    /// Implies '-v'/'--verbose'.
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+
     /// Do not prompt before emptying the trash.
     #[arg(long, short = 'f')]
     pub force: bool,
@@ -0,0 +1,67 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Colors.
+
+use std::{
+    ffi::OsString,
+    fs::{FileType, Metadata},
+    path::PathBuf,
+};
+
+use iron_bin::TrashEntry;
+use lscolors::{Colorable, LsColors};
+
+/// File of a trash entry, as seen by `LsColors`.
+///
+/// The type and metadata are those of the file in the trash,
+/// whereas the name is the original one, so that extensions are taken into account.
+struct EntryFile<'a> {
+    entry: &'a TrashEntry,
+    metadata: Option<Metadata>,
+}
+
+impl Colorable for EntryFile<'_> {
+    fn path(&self) -> PathBuf {
+        self.entry.file_path().into()
+    }
+
+    fn file_name(&self) -> OsString {
+        self.entry
+            .original_path()
+            .file_name()
+            .unwrap_or_default()
+            .into()
+    }
+
+    fn file_type(&self) -> Option<FileType> {
+        self.metadata.as_ref().map(Metadata::file_type)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.metadata.clone()
+    }
+}
+
+/// Paint the given text, representing the given entry, according to the given colors.
+///
+/// Symbolic links whose target doesn't exist are painted as orphaned symbolic links.
+// NOTE: Entries whose file is missing from the trash are broken, so they are reported rather than listed
+pub(crate) fn paint_entry(ls_colors: &LsColors, entry: &TrashEntry, text: String) -> String {
+    let metadata = entry.metadata().ok();
+    match ls_colors.style_for(&EntryFile { entry, metadata }) {
+        Some(style) => style.to_nu_ansi_term_style().paint(text).to_string(),
+        None => text,
+    }
+}
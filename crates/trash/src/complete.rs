@@ -0,0 +1,81 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Dynamic shell completion.
+
+use std::{collections::BTreeSet, ffi::OsStr};
+
+use anyhow::Result;
+use camino::Utf8PathBuf;
+use clap_complete::CompletionCandidate;
+use iron_bin::Trash;
+
+use crate::app::trash_from_env;
+
+/// Complete the original paths of the files in the trash.
+///
+/// Used by the `restore`, `rm` and `info` commands.
+/// The '--trash-dir' option is not available during completion, but [`TRASH_DIR_ENV`](crate::cli::TRASH_DIR_ENV) is.
+/// Errors are ignored, as there is no way to report them during completion.
+pub(crate) fn complete_trashed_paths(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(prefix) = current.to_str() else {
+        return Vec::new();
+    };
+    trash_from_env()
+        .and_then(|trash| trashed_paths(&trash, prefix))
+        .unwrap_or_default()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// Return the distinct original paths of the files in a trash that start with a prefix, in order.
+///
+/// Broken entries are skipped.
+fn trashed_paths(trash: &Trash, prefix: &str) -> Result<BTreeSet<Utf8PathBuf>> {
+    Ok(trash
+        .entries()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.original_path().to_owned())
+        .filter(|path| path.as_str().starts_with(prefix))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{TempDir, prelude::*};
+    use camino::Utf8Path;
+
+    use super::*;
+
+    #[test]
+    fn test_trashed_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(temp_dir.path()).unwrap();
+        let trash = Trash::new(dir.join("trash"));
+        for name in ["a.txt", "b.txt", "a.txt", "c.md"] {
+            temp_dir.child(name).write_str(name).unwrap();
+            trash.put(dir.join(name)).unwrap();
+        }
+        assert_eq!(
+            trashed_paths(&trash, "").unwrap(),
+            BTreeSet::from(["a.txt", "b.txt", "c.md"].map(|name| dir.join(name)))
+        );
+        assert_eq!(
+            trashed_paths(&trash, dir.join("a").as_str()).unwrap(),
+            BTreeSet::from([dir.join("a.txt")])
+        );
+        assert!(trashed_paths(&trash, "/nonexistent/").unwrap().is_empty());
+    }
+}
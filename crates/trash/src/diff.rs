@@ -0,0 +1,313 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of trashed files with current files.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, Read, Write},
+};
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use sha2::{Digest, Sha256};
+use similar::TextDiff;
+
+/// Number of bytes examined to determine whether a file is text.
+const TEXT_PROBE_LEN: u64 = 8000;
+
+/// Kind of file, as far as the comparison is concerned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FileKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+impl FileKind {
+    fn of(path: impl AsRef<Utf8Path>) -> io::Result<Self> {
+        let file_type = path.as_ref().symlink_metadata()?.file_type();
+        let kind = if file_type.is_dir() {
+            Self::Dir
+        } else if file_type.is_symlink() {
+            Self::Symlink
+        } else {
+            Self::File
+        };
+        Ok(kind)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::File => "file",
+            Self::Dir => "directory",
+            Self::Symlink => "symbolic link",
+        }
+    }
+}
+
+/// Compare the given trashed file with the given current file, and write the differences to the given writer.
+///
+/// Text files are compared line by line, as a unified diff.
+/// Binary files are summarized by their sizes and checksums.
+/// Directories are compared file by file, recursively.
+/// Nothing is written if the files are identical.
+pub(crate) fn diff(
+    old_path: impl AsRef<Utf8Path>,
+    old_label: impl AsRef<str>,
+    new_path: impl AsRef<Utf8Path>,
+    new_label: impl AsRef<str>,
+    writer: &mut impl Write,
+) -> Result<()> {
+    let old_path = old_path.as_ref();
+    let old_label = old_label.as_ref();
+    let new_path = new_path.as_ref();
+    let new_label = new_label.as_ref();
+    let old_kind =
+        FileKind::of(old_path).with_context(|| format!("cannot get metadata of {old_path}"))?;
+    let new_kind =
+        FileKind::of(new_path).with_context(|| format!("cannot get metadata of {new_path}"))?;
+    match (old_kind, new_kind) {
+        (FileKind::File, FileKind::File) => {
+            diff_files(old_path, old_label, new_path, new_label, writer)
+        }
+        (FileKind::Dir, FileKind::Dir) => diff_dirs(old_path, new_path, writer),
+        (FileKind::Symlink, FileKind::Symlink) => {
+            let old_target = read_link(old_path)?;
+            let new_target = read_link(new_path)?;
+            if old_target != new_target {
+                writeln!(writer, "symbolic links differ")?;
+                writeln!(writer, "  {old_label}: -> {old_target}")?;
+                writeln!(writer, "  {new_label}: -> {new_target}")?;
+            }
+            Ok(())
+        }
+        _ => {
+            writeln!(writer, "file types differ")?;
+            writeln!(writer, "  {old_label}: {}", old_kind.name())?;
+            writeln!(writer, "  {new_label}: {}", new_kind.name())?;
+            Ok(())
+        }
+    }
+}
+
+fn diff_files(
+    old_path: &Utf8Path,
+    old_label: &str,
+    new_path: &Utf8Path,
+    new_label: &str,
+    writer: &mut impl Write,
+) -> Result<()> {
+    // Compare as text, if possible
+    if let Some(old_text) = read_text(old_path)?
+        && let Some(new_text) = read_text(new_path)?
+    {
+        let text_diff = TextDiff::from_lines(&old_text, &new_text);
+        write!(
+            writer,
+            "{}",
+            text_diff.unified_diff().header(old_label, new_label)
+        )?;
+        return Ok(());
+    }
+    // Compare as binary
+    let old_summary = summary(old_path)?;
+    let new_summary = summary(new_path)?;
+    if old_summary != new_summary {
+        let (old_size, old_checksum) = old_summary;
+        let (new_size, new_checksum) = new_summary;
+        writeln!(writer, "binary files differ")?;
+        writeln!(
+            writer,
+            "  {old_label}: {old_size} bytes, sha256 {old_checksum}"
+        )?;
+        writeln!(
+            writer,
+            "  {new_label}: {new_size} bytes, sha256 {new_checksum}"
+        )?;
+    }
+    Ok(())
+}
+
+fn diff_dirs(old_dir: &Utf8Path, new_dir: &Utf8Path, writer: &mut impl Write) -> Result<()> {
+    let old_paths = relative_paths(old_dir)?;
+    let new_paths = relative_paths(new_dir)?;
+    for path in old_paths.union(&new_paths) {
+        match (old_paths.contains(path), new_paths.contains(path)) {
+            (true, false) => writeln!(writer, "only in trash: {path}")?,
+            (false, true) => writeln!(writer, "only in current: {path}")?,
+            _ => {
+                let old_path = old_dir.join(path);
+                let new_path = new_dir.join(path);
+                let old_kind = FileKind::of(&old_path)?;
+                let new_kind = FileKind::of(&new_path)?;
+                let differ = match (old_kind, new_kind) {
+                    // NOTE: The contents of directories are compared separately
+                    (FileKind::Dir, FileKind::Dir) => false,
+                    (FileKind::File, FileKind::File) => summary(&old_path)? != summary(&new_path)?,
+                    (FileKind::Symlink, FileKind::Symlink) => {
+                        read_link(&old_path)? != read_link(&new_path)?
+                    }
+                    _ => true,
+                };
+                if differ {
+                    writeln!(writer, "differ: {path}")?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Return the paths of the files inside the given directory, recursively, relative to it.
+///
+/// Symbolic links are not followed.
+fn relative_paths(dir: &Utf8Path) -> Result<BTreeSet<Utf8PathBuf>> {
+    fn collect(
+        dir: &Utf8Path,
+        base_dir: &Utf8Path,
+        paths: &mut BTreeSet<Utf8PathBuf>,
+    ) -> Result<()> {
+        for dir_entry in dir
+            .read_dir_utf8()
+            .with_context(|| format!("cannot read directory {dir}"))?
+        {
+            let dir_entry = dir_entry.with_context(|| format!("cannot read directory {dir}"))?;
+            let path = dir_entry.path();
+            let relative_path = path
+                .strip_prefix(base_dir)
+                .expect("path is not inside base directory");
+            paths.insert(relative_path.to_owned());
+            if dir_entry.file_type()?.is_dir() {
+                collect(path, base_dir, paths)?;
+            }
+        }
+        Ok(())
+    }
+
+    let mut paths = BTreeSet::new();
+    collect(dir, dir, &mut paths)?;
+    Ok(paths)
+}
+
+/// Return the contents of the given file if it is a text file.
+///
+/// A file is considered text if its first bytes contain no NUL byte and it is valid UTF-8.
+fn read_text(path: &Utf8Path) -> Result<Option<String>> {
+    let mut file = fs::File::open(path).with_context(|| format!("cannot open file {path}"))?;
+    let mut probe = Vec::new();
+    (&mut file)
+        .take(TEXT_PROBE_LEN)
+        .read_to_end(&mut probe)
+        .with_context(|| format!("cannot read file {path}"))?;
+    if probe.contains(&0) {
+        return Ok(None);
+    }
+    let mut contents = probe;
+    file.read_to_end(&mut contents)
+        .with_context(|| format!("cannot read file {path}"))?;
+    Ok(String::from_utf8(contents).ok())
+}
+
+/// Return the size and the SHA-256 checksum of the given file.
+fn summary(path: &Utf8Path) -> Result<(u64, String)> {
+    let mut file = fs::File::open(path).with_context(|| format!("cannot open file {path}"))?;
+    let mut hasher = Sha256::new();
+    let size =
+        io::copy(&mut file, &mut hasher).with_context(|| format!("cannot read file {path}"))?;
+    let checksum = format!("{:x}", hasher.finalize());
+    Ok((size, checksum))
+}
+
+fn read_link(path: &Utf8Path) -> Result<Utf8PathBuf> {
+    path.read_link_utf8()
+        .with_context(|| format!("cannot read symbolic link {path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{TempDir, prelude::*};
+
+    use super::*;
+
+    fn diff_to_string(old_path: &Utf8Path, new_path: &Utf8Path) -> String {
+        let mut writer = Vec::new();
+        diff(old_path, "old", new_path, "new", &mut writer).unwrap();
+        String::from_utf8(writer).unwrap()
+    }
+
+    fn temp_dir_path(temp_dir: &TempDir) -> &Utf8Path {
+        Utf8Path::from_path(temp_dir.path()).unwrap()
+    }
+
+    #[test]
+    fn test_diff_text_files() {
+        let temp_dir = TempDir::new().unwrap();
+        temp_dir.child("old.txt").write_str("a\nb\nc\n").unwrap();
+        temp_dir.child("new.txt").write_str("a\nB\nc\n").unwrap();
+        let dir = temp_dir_path(&temp_dir);
+        assert_eq!(
+            diff_to_string(&dir.join("old.txt"), &dir.join("new.txt")),
+            "--- old\n+++ new\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n"
+        );
+        assert_eq!(
+            diff_to_string(&dir.join("old.txt"), &dir.join("old.txt")),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_diff_binary_files() {
+        let temp_dir = TempDir::new().unwrap();
+        temp_dir.child("old.bin").write_binary(b"a\0b").unwrap();
+        temp_dir.child("new.bin").write_binary(b"a\0bc").unwrap();
+        let dir = temp_dir_path(&temp_dir);
+        let output = diff_to_string(&dir.join("old.bin"), &dir.join("new.bin"));
+        assert!(output.starts_with("binary files differ\n"));
+        assert!(output.contains("  old: 3 bytes, sha256 "));
+        assert!(output.contains("  new: 4 bytes, sha256 "));
+    }
+
+    #[test]
+    fn test_diff_dirs() {
+        let temp_dir = TempDir::new().unwrap();
+        temp_dir.child("old/same.txt").write_str("abc").unwrap();
+        temp_dir.child("old/changed.txt").write_str("abc").unwrap();
+        temp_dir.child("old/removed.txt").write_str("abc").unwrap();
+        temp_dir.child("new/same.txt").write_str("abc").unwrap();
+        temp_dir.child("new/changed.txt").write_str("abd").unwrap();
+        temp_dir
+            .child("new/sub/added.txt")
+            .write_str("abc")
+            .unwrap();
+        let dir = temp_dir_path(&temp_dir);
+        assert_eq!(
+            diff_to_string(&dir.join("old"), &dir.join("new")),
+            "differ: changed.txt\nonly in trash: removed.txt\nonly in current: sub\nonly in current: sub/added.txt\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_file_types() {
+        let temp_dir = TempDir::new().unwrap();
+        temp_dir.child("file").write_str("abc").unwrap();
+        temp_dir.child("dir").create_dir_all().unwrap();
+        let dir = temp_dir_path(&temp_dir);
+        assert_eq!(
+            diff_to_string(&dir.join("file"), &dir.join("dir")),
+            "file types differ\n  old: file\n  new: directory\n"
+        );
+    }
+}
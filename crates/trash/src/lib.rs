@@ -14,3 +14,11 @@
 
 pub mod app;
 pub mod cli;
+mod color;
+mod complete;
+mod diff;
+mod pattern;
+mod picker;
+pub mod rmtrash;
+mod size;
+mod time;
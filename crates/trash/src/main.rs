@@ -14,7 +14,12 @@
 
 use std::process::ExitCode;
 
-use trash::app::App;
+use clap::CommandFactory;
+use clap_complete::CompleteEnv;
+use trash::{
+    app::{App, BrokenEntries},
+    cli::Cli,
+};
 
 /// Entry point of the `trash` command.
 ///
@@ -22,11 +27,24 @@ use trash::app::App;
 ///
 /// This function does not return a `Result` as we don't want the default error handling,
 /// i.e. printing something like `"Error: {:?}"`.
+///
+/// If the trash contains broken entries but the command otherwise succeeded,
+/// a warning is printed and the exit status is [`BrokenEntries::EXIT_STATUS`].
+///
+/// # Shell completion
+///
+/// If the `COMPLETE` environment variable is set, completions are printed instead (see [`CompleteEnv`]).
 fn main() -> ExitCode {
-    if let Err(err) = App::run() {
-        eprintln!("error: {err:#}");
-        ExitCode::FAILURE
-    } else {
-        ExitCode::SUCCESS
+    CompleteEnv::with_factory(Cli::command).complete();
+    match App::run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) if err.is::<BrokenEntries>() => {
+            eprintln!("warning: {err:#}");
+            ExitCode::from(BrokenEntries::EXIT_STATUS)
+        }
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            ExitCode::FAILURE
+        }
     }
 }
@@ -0,0 +1,135 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Path patterns.
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+
+/// Options used to match glob patterns.
+///
+/// Wildcards don't match path separators, like in the shell.
+const GLOB_MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Pattern matching original paths.
+#[derive(Clone, Debug)]
+pub(crate) enum PathPattern {
+    /// Glob pattern.
+    ///
+    /// The pattern also matches the path it consists of literally,
+    /// so that paths containing special characters can still be matched.
+    Glob(Utf8PathBuf, Pattern),
+
+    /// Regular expression, which must match the whole path.
+    Regex(Regex),
+
+    /// Literal path.
+    Literal(Utf8PathBuf),
+}
+
+impl PathPattern {
+    /// Create a glob pattern relative to the given directory.
+    ///
+    /// The special characters of the directory are matched literally.
+    /// If the pattern is absolute, the directory is ignored.
+    pub(crate) fn glob_in(dir: &Utf8Path, pattern: &Utf8Path) -> Result<Self> {
+        let path = dir.join(pattern);
+        let glob_pattern = Utf8Path::new(&Pattern::escape(dir.as_str())).join(pattern);
+        let glob = Pattern::new(glob_pattern.as_str())
+            .with_context(|| format!("invalid glob pattern: {pattern}"))?;
+        Ok(Self::Glob(path, glob))
+    }
+
+    /// Create a regular expression pattern.
+    pub(crate) fn regex(pattern: impl AsRef<str>) -> Result<Self> {
+        let pattern = pattern.as_ref();
+        let regex = Regex::new(&format!("^(?:{pattern})$"))
+            .with_context(|| format!("invalid regular expression: {pattern}"))?;
+        Ok(Self::Regex(regex))
+    }
+
+    /// Create a pattern that matches only the given path.
+    pub(crate) fn literal(path: impl Into<Utf8PathBuf>) -> Self {
+        Self::Literal(path.into())
+    }
+
+    pub(crate) fn is_regex(&self) -> bool {
+        matches!(self, Self::Regex(_))
+    }
+
+    /// Return whether this pattern matches the given path.
+    pub(crate) fn matches(&self, path: impl AsRef<Utf8Path>) -> bool {
+        let path = path.as_ref();
+        match self {
+            Self::Glob(pattern, glob) => {
+                path == pattern || glob.matches_with(path.as_str(), GLOB_MATCH_OPTIONS)
+            }
+            Self::Regex(regex) => regex.is_match(path.as_str()),
+            Self::Literal(literal) => path == literal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob() {
+        let pattern =
+            PathPattern::glob_in(Utf8Path::new("/"), Utf8Path::new("/abc/*.jpg")).unwrap();
+        assert!(pattern.matches("/abc/def.jpg"));
+        assert!(!pattern.matches("/abc/def.png"));
+        assert!(!pattern.matches("/abc/def/ghi.jpg"));
+    }
+
+    #[test]
+    fn test_glob_literal() {
+        let pattern =
+            PathPattern::glob_in(Utf8Path::new("/"), Utf8Path::new("/abc/[def].jpg")).unwrap();
+        assert!(pattern.matches("/abc/[def].jpg"));
+        assert!(pattern.matches("/abc/d.jpg"));
+    }
+
+    #[test]
+    fn test_glob_in() {
+        let pattern = PathPattern::glob_in(Utf8Path::new("/a[1]"), Utf8Path::new("*.jpg")).unwrap();
+        assert!(pattern.matches("/a[1]/def.jpg"));
+        assert!(!pattern.matches("/a1/def.jpg"));
+        let pattern =
+            PathPattern::glob_in(Utf8Path::new("/a[1]"), Utf8Path::new("/abc/*.jpg")).unwrap();
+        assert!(pattern.matches("/abc/def.jpg"));
+    }
+
+    #[test]
+    fn test_literal() {
+        let pattern = PathPattern::literal("/abc/[def].jpg");
+        assert!(pattern.matches("/abc/[def].jpg"));
+        assert!(!pattern.matches("/abc/d.jpg"));
+    }
+
+    #[test]
+    fn test_regex() {
+        let pattern = PathPattern::regex(r"/abc/.*\.(jpg|png)").unwrap();
+        assert!(pattern.matches("/abc/def.jpg"));
+        assert!(pattern.matches("/abc/def/ghi.png"));
+        assert!(!pattern.matches("/xyz/abc/def.jpg"));
+    }
+}
@@ -0,0 +1,565 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Interactive full-screen picker of entries.
+
+use std::{collections::HashSet, fs, io, io::Read};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+use humansize::{DECIMAL, FormatSizeOptions, format_size};
+use iron_bin::{TrashEntry, TrashFileType};
+use ratatui::{
+    Frame, Terminal,
+    backend::Backend,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table, TableState, Wrap},
+};
+
+use crate::app::comparator;
+use crate::cli::{SortOrder, TimeStyle};
+use crate::time::format_time;
+
+/// Number of rows moved by the page up and page down keys.
+const PAGE_SIZE: usize = 10;
+
+/// Maximum number of bytes of a file shown in the preview pane.
+const PREVIEW_MAX_LEN: u64 = 4096;
+
+/// Help line displayed at the bottom of the picker.
+const HELP: &str =
+    "tab: select  ^a: select all  ^s: sort  ^r: reverse  ^u: clear  enter: restore  esc: cancel";
+
+/// Pick entries in a full-screen terminal UI.
+///
+/// Return the picked entries, or `None` if the user cancelled.
+pub(crate) fn pick(
+    entries: Vec<TrashEntry>,
+    sort_order: SortOrder,
+) -> Result<Option<Vec<TrashEntry>>> {
+    let mut terminal = ratatui::try_init().context("cannot initialize terminal")?;
+    let result = Picker::new(entries, sort_order).run(&mut terminal, event::read);
+    ratatui::try_restore().context("cannot restore terminal")?;
+    result
+}
+
+/// What to do after a key press.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Flow {
+    Continue,
+    Confirm,
+    Cancel,
+}
+
+/// Picker of entries, with fuzzy search and multiple selection.
+pub(crate) struct Picker {
+    /// Entries, sorted according to the sort order.
+    entries: Vec<TrashEntry>,
+    /// Indices of the entries that match the search, in order.
+    matches: Vec<usize>,
+    search: String,
+    /// Identifiers of the selected entries.
+    selected: HashSet<String>,
+    sort_order: SortOrder,
+    reverse: bool,
+    table_state: TableState,
+    matcher: SkimMatcherV2,
+}
+
+impl Picker {
+    pub(crate) fn new(entries: Vec<TrashEntry>, sort_order: SortOrder) -> Self {
+        let mut picker = Self {
+            entries,
+            matches: Vec::new(),
+            search: String::new(),
+            selected: HashSet::new(),
+            sort_order,
+            reverse: false,
+            table_state: TableState::default(),
+            matcher: SkimMatcherV2::default(),
+        };
+        picker.sort();
+        picker
+    }
+
+    /// Run the picker on a terminal, reading events with the given function.
+    ///
+    /// Return the picked entries, or `None` if the user cancelled.
+    pub(crate) fn run<B: Backend>(
+        mut self,
+        terminal: &mut Terminal<B>,
+        mut read_event: impl FnMut() -> io::Result<Event>,
+    ) -> Result<Option<Vec<TrashEntry>>> {
+        loop {
+            terminal
+                .draw(|frame| self.render(frame))
+                .context("cannot draw picker")?;
+            let event = read_event().context("cannot read event")?;
+            if let Event::Key(key) = event
+                && key.kind == KeyEventKind::Press
+            {
+                match self.handle_key(key) {
+                    Flow::Continue => {}
+                    Flow::Confirm => return Ok(Some(self.picked())),
+                    Flow::Cancel => return Ok(None),
+                }
+            }
+        }
+    }
+
+    /// Return the index of the current entry, if any.
+    fn current(&self) -> Option<usize> {
+        self.table_state
+            .selected()
+            .and_then(|row| self.matches.get(row).copied())
+    }
+
+    /// Return the picked entries, in order: the selected entries, or else the current entry.
+    fn picked(&self) -> Vec<TrashEntry> {
+        if self.selected.is_empty() {
+            self.current()
+                .map(|index| vec![self.entries[index].clone()])
+                .unwrap_or_default()
+        } else {
+            self.entries
+                .iter()
+                .filter(|entry| self.selected.contains(entry.identifier()))
+                .cloned()
+                .collect()
+        }
+    }
+
+    /// Sort the entries according to the sort order, then update the matches.
+    fn sort(&mut self) {
+        self.entries.sort_by(comparator(&self.sort_order));
+        if self.reverse {
+            self.entries.reverse();
+        }
+        self.update_matches();
+    }
+
+    /// Update the matches according to the search, keeping the current entry if it still matches.
+    fn update_matches(&mut self) {
+        let current = self
+            .current()
+            .map(|index| self.entries[index].identifier().to_string());
+        self.matches = (0..self.entries.len())
+            .filter(|&index| {
+                self.matcher
+                    .fuzzy_match(self.entries[index].original_path().as_str(), &self.search)
+                    .is_some()
+            })
+            .collect();
+        let row = current
+            .and_then(|identifier| {
+                self.matches
+                    .iter()
+                    .position(|&index| self.entries[index].identifier() == identifier)
+            })
+            .unwrap_or(0);
+        self.table_state
+            .select((!self.matches.is_empty()).then_some(row));
+    }
+
+    /// Move the cursor by the given number of rows, within the matches.
+    fn move_by(&mut self, delta: isize) {
+        if let Some(row) = self.table_state.selected() {
+            let last = self.matches.len().saturating_sub(1);
+            self.table_state
+                .select(Some(row.saturating_add_signed(delta).min(last)));
+        }
+    }
+
+    /// Toggle the selection of the current entry.
+    fn toggle_current(&mut self) {
+        if let Some(index) = self.current() {
+            let identifier = self.entries[index].identifier();
+            if !self.selected.remove(identifier) {
+                self.selected.insert(identifier.to_string());
+            }
+        }
+    }
+
+    /// Select all the matches, or deselect them if they are all selected.
+    fn toggle_all(&mut self) {
+        let identifiers = self
+            .matches
+            .iter()
+            .map(|&index| self.entries[index].identifier().to_string())
+            .collect::<Vec<_>>();
+        if identifiers
+            .iter()
+            .all(|identifier| self.selected.contains(identifier))
+        {
+            for identifier in identifiers {
+                self.selected.remove(&identifier);
+            }
+        } else {
+            self.selected.extend(identifiers);
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Flow {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return Flow::Cancel,
+            KeyCode::Char('c') if control => return Flow::Cancel,
+            KeyCode::Enter if self.current().is_some() || !self.selected.is_empty() => {
+                return Flow::Confirm;
+            }
+            KeyCode::Up => self.move_by(-1),
+            KeyCode::Char('p') if control => self.move_by(-1),
+            KeyCode::Down => self.move_by(1),
+            KeyCode::Char('n') if control => self.move_by(1),
+            KeyCode::PageUp => self.move_by(-(PAGE_SIZE as isize)),
+            KeyCode::PageDown => self.move_by(PAGE_SIZE as isize),
+            KeyCode::Tab => {
+                self.toggle_current();
+                self.move_by(1);
+            }
+            KeyCode::BackTab => {
+                self.toggle_current();
+                self.move_by(-1);
+            }
+            KeyCode::Char('a') if control => self.toggle_all(),
+            KeyCode::Char('s') if control => {
+                let sort_orders = [
+                    SortOrder::Path,
+                    SortOrder::Date,
+                    SortOrder::Size,
+                    SortOrder::Name,
+                    SortOrder::Trash,
+                ];
+                let index = sort_orders
+                    .iter()
+                    .position(|sort_order| *sort_order == self.sort_order)
+                    .unwrap_or(0);
+                self.sort_order = sort_orders[(index + 1) % sort_orders.len()];
+                self.sort();
+            }
+            KeyCode::Char('r') if control => {
+                self.reverse = !self.reverse;
+                self.sort();
+            }
+            KeyCode::Char('u') if control => {
+                self.search.clear();
+                self.update_matches();
+            }
+            KeyCode::Backspace => {
+                self.search.pop();
+                self.update_matches();
+            }
+            KeyCode::Char(c) if !control => {
+                self.search.push(c);
+                self.update_matches();
+            }
+            _ => {}
+        }
+        Flow::Continue
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [search_area, main_area, help_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(main_area);
+        // Search
+        let prompt = format!("> {}", self.search);
+        frame.set_cursor_position((search_area.x + prompt.chars().count() as u16, search_area.y));
+        frame.render_widget(Paragraph::new(prompt), search_area);
+        // List
+        self.render_list(frame, list_area);
+        // Preview
+        let preview = self
+            .current()
+            .map(|index| preview(&self.entries[index]))
+            .unwrap_or_default();
+        frame.render_widget(
+            Paragraph::new(preview)
+                .block(Block::bordered().title(" preview "))
+                .wrap(Wrap { trim: false }),
+            preview_area,
+        );
+        // Help
+        frame.render_widget(
+            Paragraph::new(HELP).style(Style::default().add_modifier(Modifier::DIM)),
+            help_area,
+        );
+    }
+
+    fn render_list(&mut self, frame: &mut Frame, area: Rect) {
+        let now = Local::now().naive_local();
+        let size_format = FormatSizeOptions::from(DECIMAL).space_after_value(false);
+        let rows = self.matches.iter().map(|&index| {
+            let entry = &self.entries[index];
+            let mark = if self.selected.contains(entry.identifier()) {
+                "*"
+            } else {
+                " "
+            };
+            Row::new([
+                mark.to_string(),
+                format_size(entry.size(), size_format),
+                format_time(entry.deletion_time(), &TimeStyle::Iso, &now),
+                entry.original_path().to_string(),
+            ])
+        });
+        let sort_order = format!("{:?}", self.sort_order).to_lowercase();
+        let reverse = if self.reverse { ", reversed" } else { "" };
+        let title = format!(
+            " {}/{} entries, {} selected, sorted by {sort_order}{reverse} ",
+            self.matches.len(),
+            self.entries.len(),
+            self.selected.len(),
+        );
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(1),
+                Constraint::Length(6),
+                Constraint::Length(16),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(
+            Row::new(["", "size", "deletion time", "original path"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::bordered().title(title))
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, area, &mut self.table_state);
+    }
+}
+
+/// Return the lines of the preview of an entry: its details, then its contents.
+fn preview(entry: &TrashEntry) -> Vec<Line<'static>> {
+    let now = Local::now().naive_local();
+    let file_type = entry.file_type();
+    let mut lines = vec![
+        Line::from(entry.original_path().to_string()),
+        Line::from(format!(
+            "deleted {}",
+            format_time(entry.deletion_time(), &TimeStyle::FullIso, &now)
+        )),
+        Line::from(format!("{} bytes, {file_type}", entry.size())),
+        Line::from(format!("identifier {}", entry.identifier())),
+        Line::default(),
+    ];
+    let file_path = entry.file_path();
+    let contents = match file_type {
+        TrashFileType::Dir => fs::read_dir(&file_path).map(|dir_entries| {
+            let mut names = dir_entries
+                .filter_map(|dir_entry| dir_entry.ok())
+                .map(|dir_entry| {
+                    let name = dir_entry.file_name().to_string_lossy().into_owned();
+                    if dir_entry
+                        .file_type()
+                        .is_ok_and(|file_type| file_type.is_dir())
+                    {
+                        format!("{name}/")
+                    } else {
+                        name
+                    }
+                })
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        }),
+        TrashFileType::Symlink => {
+            fs::read_link(&file_path).map(|target| vec![format!("-> {}", target.display())])
+        }
+        TrashFileType::File => read_text(&file_path).map(|text| match text {
+            Some(text) => text.lines().map(str::to_string).collect(),
+            None => vec!["binary file".to_string()],
+        }),
+    };
+    match contents {
+        Ok(contents) => lines.extend(contents.into_iter().map(Line::from)),
+        Err(err) => lines.push(Line::from(format!("cannot read {file_path}: {err}"))),
+    }
+    lines
+}
+
+/// Read the beginning of a file as text, or return `None` if it is binary.
+fn read_text(path: impl AsRef<std::path::Path>) -> io::Result<Option<String>> {
+    let mut bytes = Vec::new();
+    fs::File::open(path)?
+        .take(PREVIEW_MAX_LEN)
+        .read_to_end(&mut bytes)?;
+    if bytes.contains(&0) {
+        return Ok(None);
+    }
+    // NOTE: The file may be truncated in the middle of a character
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{TempDir, prelude::*};
+    use camino::Utf8Path;
+    use iron_bin::Trash;
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    /// Put files with the given names and contents in a new trash, and return its entries.
+    fn test_entries(temp_dir: &TempDir, files: &[(&str, &str)]) -> Vec<TrashEntry> {
+        let dir = Utf8Path::from_path(temp_dir.path()).unwrap();
+        let trash = Trash::new(dir.join("trash"));
+        for (name, contents) in files {
+            temp_dir.child(name).write_str(contents).unwrap();
+            trash.put(dir.join(name)).unwrap();
+        }
+        trash.entries().unwrap().map(Result::unwrap).collect()
+    }
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn control_key(c: char) -> Event {
+        Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+    }
+
+    /// Run a picker with the given events, and return the file names of the picked entries.
+    fn run_picker(picker: Picker, events: Vec<Event>) -> Option<Vec<String>> {
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        let mut events = events.into_iter();
+        picker
+            .run(&mut terminal, || Ok(events.next().expect("no more events")))
+            .unwrap()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .map(|entry| entry.original_path().file_name().unwrap().to_string())
+                    .collect()
+            })
+    }
+
+    /// Render a picker, and return the lines of the screen.
+    fn render_picker(picker: &mut Picker) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+        terminal.draw(|frame| picker.render(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|cells| cells.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_picker_search_and_select() {
+        let temp_dir = TempDir::new().unwrap();
+        let entries = test_entries(
+            &temp_dir,
+            &[("a_1.txt", "a"), ("b.md", "b"), ("c_1.txt", "c")],
+        );
+        let picker = Picker::new(entries.clone(), SortOrder::Path);
+        assert_eq!(
+            run_picker(picker, vec![key(KeyCode::Down), key(KeyCode::Enter)]),
+            Some(vec!["b.md".to_string()])
+        );
+        let picker = Picker::new(entries.clone(), SortOrder::Path);
+        // NOTE: The search must not match the random name of the temporary directory,
+        // which only contains alphanumeric characters
+        let mut events = "_1"
+            .chars()
+            .map(|c| key(KeyCode::Char(c)))
+            .collect::<Vec<_>>();
+        events.extend([key(KeyCode::Tab), key(KeyCode::Tab), key(KeyCode::Enter)]);
+        assert_eq!(
+            run_picker(picker, events),
+            Some(vec!["a_1.txt".to_string(), "c_1.txt".to_string()])
+        );
+        let picker = Picker::new(entries, SortOrder::Path);
+        assert_eq!(
+            run_picker(
+                picker,
+                vec![control_key('a'), control_key('r'), key(KeyCode::Enter)]
+            ),
+            Some(vec![
+                "c_1.txt".to_string(),
+                "b.md".to_string(),
+                "a_1.txt".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_picker_cancel() {
+        let temp_dir = TempDir::new().unwrap();
+        let entries = test_entries(&temp_dir, &[("a.txt", "a")]);
+        let picker = Picker::new(entries.clone(), SortOrder::Path);
+        assert_eq!(run_picker(picker, vec![key(KeyCode::Esc)]), None);
+        let picker = Picker::new(entries, SortOrder::Path);
+        assert_eq!(
+            run_picker(
+                picker,
+                vec![
+                    key(KeyCode::Char('#')),
+                    key(KeyCode::Enter),
+                    control_key('c')
+                ]
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_picker_sort() {
+        let temp_dir = TempDir::new().unwrap();
+        let entries = test_entries(&temp_dir, &[("a.txt", "a"), ("b.txt", "bbb")]);
+        let mut picker = Picker::new(entries, SortOrder::Date);
+        picker.handle_key(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert_eq!(picker.sort_order, SortOrder::Size);
+        assert_eq!(
+            picker.picked()[0].original_path().file_name(),
+            Some("b.txt")
+        );
+    }
+
+    #[test]
+    fn test_picker_render() {
+        let temp_dir = TempDir::new().unwrap();
+        let entries = test_entries(&temp_dir, &[("a.txt", "hello\nworld\n")]);
+        let path = entries[0].original_path().to_string();
+        let deletion_time = entries[0]
+            .deletion_time()
+            .format("%Y-%m-%d %H:%M")
+            .to_string();
+        let mut picker = Picker::new(entries, SortOrder::Path);
+        picker.handle_key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE));
+        let lines = render_picker(&mut picker);
+        assert!(lines[0].starts_with("> a "));
+        assert!(lines[1].contains("1/1 entries, 0 selected, sorted by path"));
+        assert!(lines[1].contains("preview"));
+        assert!(lines[2].contains("size   deletion time    original path"));
+        assert!(lines[3].contains(&format!("12B    {}", &deletion_time)));
+        assert!(lines[3].contains(&path));
+        assert!(lines[11].starts_with("tab: select"));
+        let contents = lines.join("\n");
+        assert!(contents.contains("hello"), "{contents}");
+        assert!(contents.contains("world"), "{contents}");
+    }
+}
@@ -0,0 +1,513 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `rm`-compatible front end.
+//!
+//! The `rmtrash` command accepts the options of the `rm` command,
+//! but puts the files in the trash instead of removing them.
+//! Its messages and exit status are those of `rm`, so that it can be used as an alias.
+//! As it has no '--trash-dir' option, the trash is only given by the environment.
+
+use std::{
+    env,
+    fs::{self, Metadata},
+    io::{self, IsTerminal, stdin},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{FileTypeExt, MetadataExt},
+    },
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use camino::Utf8Path;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
+use iron_bin::Trash;
+use prompt::Prompter;
+
+use crate::app::trash_from_env;
+
+/// Put files in the trash, with the options of the rm command.
+///
+/// By default, directories are not put in the trash.
+#[derive(Clone, Debug, Parser, PartialEq)]
+#[command(name = "rmtrash", version)]
+pub struct RmtrashCli {
+    /// Ignore nonexistent files and arguments, never prompt.
+    #[arg(long, overrides_with_all = ["interactive", "interactive_once", "interactive_when"], short = 'f')]
+    pub force: bool,
+
+    /// Prompt before every removal.
+    #[arg(overrides_with_all = ["force", "interactive_once", "interactive_when"], short = 'i')]
+    pub interactive: bool,
+
+    /// Prompt once before removing more than three files, or when removing recursively.
+    #[arg(overrides_with_all = ["force", "interactive", "interactive_when"], short = 'I')]
+    pub interactive_once: bool,
+
+    /// Prompt according to WHEN; without WHEN, prompt always.
+    #[arg(
+        id = "interactive_when",
+        long = "interactive",
+        overrides_with_all = ["force", "interactive", "interactive_once"],
+        require_equals = true,
+        value_name = "WHEN"
+    )]
+    pub interactive_when: Option<Option<InteractiveWhen>>,
+
+    /// Put directories and their contents in the trash.
+    #[arg(long, short = 'r', short_alias = 'R')]
+    pub recursive: bool,
+
+    /// Put empty directories in the trash.
+    #[arg(long, short = 'd')]
+    pub dir: bool,
+
+    /// When removing recursively, refuse any argument that contains a file system
+    /// different from its own.
+    ///
+    /// Unlike with rm, the whole argument is refused, as a directory is put in the trash as a whole.
+    #[arg(long)]
+    pub one_file_system: bool,
+
+    /// Do not remove '/' (default).
+    ///
+    /// With 'all', reject any argument on a file system different from that of its parent.
+    #[arg(
+        long,
+        overrides_with = "no_preserve_root",
+        require_equals = true,
+        value_name = "all"
+    )]
+    pub preserve_root: Option<Option<PreserveRoot>>,
+
+    /// Do not treat '/' specially.
+    #[arg(long, overrides_with = "preserve_root")]
+    pub no_preserve_root: bool,
+
+    /// Explain what is being done.
+    #[arg(long, short = 'v')]
+    pub verbose: bool,
+
+    /// Files.
+    #[arg(value_name = "FILE")]
+    pub paths: Vec<PathBuf>,
+}
+
+/// When to prompt.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum InteractiveWhen {
+    /// Never prompt.
+    Never,
+    /// Prompt once, like '-I'.
+    Once,
+    /// Prompt before every removal, like '-i'.
+    Always,
+}
+
+/// Scope of '--preserve-root'.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, ValueEnum)]
+pub enum PreserveRoot {
+    /// Also reject arguments on a file system different from that of their parent.
+    All,
+}
+
+/// Prompting mode, resolved from the '-f', '-i', '-I' and '--interactive' options.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Prompting {
+    /// Never prompt, and ignore nonexistent files ('-f').
+    Force,
+    /// Never prompt ('--interactive=never').
+    Never,
+    /// Prompt only before write-protected files, if the standard input is a terminal (default).
+    WriteProtected,
+    /// Prompt once, then like [`Prompting::WriteProtected`] ('-I').
+    Once,
+    /// Prompt before every file ('-i').
+    Always,
+}
+
+impl RmtrashCli {
+    fn prompting(&self) -> Prompting {
+        // NOTE: The options override each other, so at most one of them is set
+        if self.force {
+            Prompting::Force
+        } else if self.interactive {
+            Prompting::Always
+        } else if self.interactive_once {
+            Prompting::Once
+        } else {
+            match self.interactive_when {
+                None => Prompting::WriteProtected,
+                Some(None | Some(InteractiveWhen::Always)) => Prompting::Always,
+                Some(Some(InteractiveWhen::Once)) => Prompting::Once,
+                Some(Some(InteractiveWhen::Never)) => Prompting::Never,
+            }
+        }
+    }
+}
+
+/// `rm`-compatible front end.
+#[derive(Clone, Debug)]
+pub struct Rmtrash {
+    /// Name of the command, used as the prefix of messages.
+    name: String,
+    cli: RmtrashCli,
+    trash: Trash,
+}
+
+impl Rmtrash {
+    /// Run the command, and return its exit status.
+    ///
+    /// As with `rm`, the exit status is 1 if any file could not be removed, or in case of usage error.
+    pub fn run() -> ExitCode {
+        let name = env::args_os()
+            .next()
+            .map(PathBuf::from)
+            .and_then(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| "rmtrash".to_string());
+        // NOTE: The name is given to clap, so that its messages use it too
+        let cli = match RmtrashCli::command()
+            .name(name.clone())
+            .try_get_matches()
+            .and_then(|matches| RmtrashCli::from_arg_matches(&matches))
+        {
+            Ok(cli) => cli,
+            Err(err) => {
+                let _ = err.print();
+                return if err.use_stderr() {
+                    ExitCode::FAILURE
+                } else {
+                    ExitCode::SUCCESS
+                };
+            }
+        };
+        let trash = match trash_from_env() {
+            Ok(trash) => trash,
+            Err(err) => {
+                eprintln!("{name}: {err:#}");
+                return ExitCode::FAILURE;
+            }
+        };
+        let rmtrash = Rmtrash {
+            name,
+            cli,
+            trash: trash.with_operation(Some(Trash::new_operation())),
+        };
+        if rmtrash.remove_all() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        }
+    }
+
+    /// Print a message prefixed with the name of the command.
+    fn error(&self, message: impl AsRef<str>) {
+        eprintln!("{}: {}", self.name, message.as_ref());
+    }
+
+    /// Prompt with a question prefixed with the name of the command.
+    ///
+    /// As with `rm`, the answer is read from the standard input, not from the terminal.
+    /// An error while prompting is treated as a no.
+    fn prompt(&self, question: impl AsRef<str>) -> bool {
+        Prompter::stdio()
+            .confirm(format!("{}: {}", self.name, question.as_ref()))
+            .unwrap_or(false)
+    }
+
+    /// Remove all the paths, and return whether they were all removed.
+    fn remove_all(&self) -> bool {
+        let cli = &self.cli;
+        let prompting = cli.prompting();
+        if cli.paths.is_empty() {
+            if prompting == Prompting::Force {
+                return true;
+            }
+            self.error("missing operand");
+            eprintln!("Try '{} --help' for more information.", self.name);
+            return false;
+        }
+        if prompting == Prompting::Once && (cli.paths.len() > 3 || cli.recursive) {
+            let count = cli.paths.len();
+            let plural = if count == 1 { "" } else { "s" };
+            let recursively = if cli.recursive { " recursively" } else { "" };
+            if !self.prompt(format!("remove {count} argument{plural}{recursively}?")) {
+                return true;
+            }
+        }
+        let mut ok = true;
+        for path in &cli.paths {
+            ok &= self.remove(path, prompting);
+        }
+        ok
+    }
+
+    /// Remove a path, and return whether it was removed, skipped on purpose or ignored.
+    fn remove(&self, path: &Path, prompting: Prompting) -> bool {
+        let cli = &self.cli;
+        let quoted = quote(path);
+        if is_dot_or_dot_dot(path) {
+            self.error(format!(
+                "refusing to remove '.' or '..' directory: skipping {quoted}"
+            ));
+            return false;
+        }
+        let metadata = match path.symlink_metadata() {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && prompting == Prompting::Force => {
+                return true;
+            }
+            Err(err) => {
+                self.error(format!(
+                    "cannot remove {quoted}: {}",
+                    io_error_message(&err)
+                ));
+                return false;
+            }
+        };
+        if metadata.is_dir() {
+            if cli.recursive {
+                if !self.check_recursive(path, &metadata) {
+                    return false;
+                }
+            } else if cli.dir {
+                if !is_empty_dir(path) {
+                    self.error(format!("cannot remove {quoted}: Directory not empty"));
+                    return false;
+                }
+            } else {
+                self.error(format!("cannot remove {quoted}: Is a directory"));
+                return false;
+            }
+        }
+        let write_protected = !metadata.is_symlink() && metadata.permissions().readonly();
+        let should_prompt = match prompting {
+            Prompting::Always => true,
+            // TODO: Check the access rights of the current user instead of the permission bits
+            Prompting::WriteProtected | Prompting::Once => write_protected && stdin().is_terminal(),
+            Prompting::Force | Prompting::Never => false,
+        };
+        if should_prompt {
+            let write_protected = if write_protected {
+                "write-protected "
+            } else {
+                ""
+            };
+            let file_type = file_type_description(&metadata);
+            if !self.prompt(format!("remove {write_protected}{file_type} {quoted}?")) {
+                return true;
+            }
+        }
+        let Some(utf8_path) = Utf8Path::from_path(path) else {
+            self.error(format!("cannot remove {quoted}: invalid UTF-8 path"));
+            return false;
+        };
+        match self.trash.put(utf8_path) {
+            Ok(_) => {
+                if cli.verbose {
+                    if metadata.is_dir() {
+                        println!("removed directory {quoted}");
+                    } else {
+                        println!("removed {quoted}");
+                    }
+                }
+                true
+            }
+            Err(err) => {
+                self.error(format!("cannot remove {quoted}: {err:#}"));
+                false
+            }
+        }
+    }
+
+    /// Check whether a directory can be removed recursively, according to the options.
+    fn check_recursive(&self, path: &Path, metadata: &Metadata) -> bool {
+        let cli = &self.cli;
+        let quoted = quote(path);
+        let preserve_root = !cli.no_preserve_root;
+        if preserve_root && fs::canonicalize(path).is_ok_and(|path| path == Path::new("/")) {
+            if path == Path::new("/") {
+                self.error(format!(
+                    "it is dangerous to operate recursively on {quoted}"
+                ));
+            } else {
+                self.error(format!(
+                    "it is dangerous to operate recursively on {quoted} (same as '/')"
+                ));
+            }
+            self.error("use --no-preserve-root to override this failsafe");
+            return false;
+        }
+        if cli.preserve_root == Some(Some(PreserveRoot::All))
+            && let Ok(parent_metadata) = path.join("..").metadata()
+            && parent_metadata.dev() != metadata.dev()
+        {
+            self.error(format!(
+                "skipping {quoted}, since it's on a different device"
+            ));
+            self.error("and --preserve-root=all is in effect");
+            return false;
+        }
+        if cli.one_file_system
+            && let Some(other_path) = find_other_device(path, metadata.dev())
+        {
+            self.error(format!(
+                "skipping {}, since it's on a different device",
+                quote(&other_path)
+            ));
+            return false;
+        }
+        true
+    }
+}
+
+/// Quote a path like `rm` does in its messages.
+fn quote(path: &Path) -> String {
+    format!("'{}'", path.display())
+}
+
+/// Return the message of an I/O error, without the OS error code.
+fn io_error_message(err: &io::Error) -> String {
+    let message = err.to_string();
+    match message.find(" (os error") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    }
+}
+
+/// Return whether the last component of a path is '.' or '..'.
+fn is_dot_or_dot_dot(path: &Path) -> bool {
+    // NOTE: `Path::components` drops trailing '.' components, so we look at the bytes
+    let bytes = path.as_os_str().as_bytes();
+    let end = bytes
+        .iter()
+        .rposition(|&byte| byte != b'/')
+        .map_or(0, |index| index + 1);
+    let start = bytes[..end]
+        .iter()
+        .rposition(|&byte| byte == b'/')
+        .map_or(0, |index| index + 1);
+    matches!(&bytes[start..end], b"." | b"..")
+}
+
+/// Return whether a directory is empty.
+fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_none())
+}
+
+/// Return the first path inside a directory that is on a device other than the given one, if any.
+///
+/// Symbolic links are not followed.
+fn find_other_device(dir: &Path, dev: u64) -> Option<PathBuf> {
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let Ok(metadata) = entry.path().symlink_metadata() else {
+            continue;
+        };
+        if metadata.dev() != dev {
+            return Some(entry.path());
+        }
+        if metadata.is_dir()
+            && let Some(path) = find_other_device(&entry.path(), dev)
+        {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Return the description of a file type used in prompts, like `rm`.
+fn file_type_description(metadata: &Metadata) -> &'static str {
+    let file_type = metadata.file_type();
+    if file_type.is_dir() {
+        "directory"
+    } else if file_type.is_symlink() {
+        "symbolic link"
+    } else if file_type.is_file() {
+        if metadata.len() == 0 {
+            "regular empty file"
+        } else {
+            "regular file"
+        }
+    } else if file_type.is_fifo() {
+        "fifo"
+    } else if file_type.is_socket() {
+        "socket"
+    } else if file_type.is_block_device() {
+        "block special file"
+    } else if file_type.is_char_device() {
+        "character special file"
+    } else {
+        "file"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> RmtrashCli {
+        RmtrashCli::parse_from(iter::once("rmtrash").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_prompting() {
+        assert_eq!(parse(&[]).prompting(), Prompting::WriteProtected);
+        assert_eq!(parse(&["-f"]).prompting(), Prompting::Force);
+        assert_eq!(parse(&["-i"]).prompting(), Prompting::Always);
+        assert_eq!(parse(&["-I"]).prompting(), Prompting::Once);
+        assert_eq!(parse(&["-f", "-i"]).prompting(), Prompting::Always);
+        assert_eq!(parse(&["-i", "-f"]).prompting(), Prompting::Force);
+        assert_eq!(parse(&["-fI"]).prompting(), Prompting::Once);
+        assert_eq!(parse(&["--interactive"]).prompting(), Prompting::Always);
+        assert_eq!(parse(&["--interactive=once"]).prompting(), Prompting::Once);
+        assert_eq!(
+            parse(&["-i", "--interactive=never"]).prompting(),
+            Prompting::Never
+        );
+    }
+
+    #[test]
+    fn test_parse_paths() {
+        let cli = parse(&["-rv", "--", "-f", "a"]);
+        assert!(cli.recursive);
+        assert!(cli.verbose);
+        assert!(!cli.force);
+        assert_eq!(cli.paths, [PathBuf::from("-f"), PathBuf::from("a")]);
+        assert!(parse(&["-R"]).recursive);
+    }
+
+    #[test]
+    fn test_is_dot_or_dot_dot() {
+        for path in [".", "..", "./", "a/.", "a/..", "a/../", "/."] {
+            assert!(is_dot_or_dot_dot(Path::new(path)), "{path}");
+        }
+        for path in ["a", ".a", "..a", "a.", "/", "a/.b/"] {
+            assert!(!is_dot_or_dot_dot(Path::new(path)), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_io_error_message() {
+        let err = io::Error::from(io::ErrorKind::NotFound);
+        assert_eq!(io_error_message(&err), "entity not found");
+        let err = io::Error::from_raw_os_error(2);
+        assert_eq!(io_error_message(&err), "No such file or directory");
+    }
+}
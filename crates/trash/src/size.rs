@@ -0,0 +1,72 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Size parsing.
+
+/// Parse a size given on the command line.
+///
+/// The size is a number of bytes, optionally followed by a decimal unit (`k`, `M`, `G` or `T`)
+/// or a binary unit (`Ki`, `Mi`, `Gi` or `Ti`), itself optionally followed by `B` (e.g. `500`, `10M` or `1GiB`).
+///
+/// This function is meant to be used as a value parser by clap.
+pub(crate) fn parse_size(s: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid size (expected e.g. '500', '10M' or '1GiB'): {s}");
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if digits == 0 {
+        return Err(invalid());
+    }
+    let value = s[..digits].parse::<u64>().map_err(|_| invalid())?;
+    let unit = &s[digits..];
+    let unit = unit.strip_suffix('B').unwrap_or(unit);
+    let multiplier: u64 = match unit {
+        "" => 1,
+        "k" | "K" => 1000,
+        "M" => 1000_u64.pow(2),
+        "G" => 1000_u64.pow(3),
+        "T" => 1000_u64.pow(4),
+        "Ki" => 1 << 10,
+        "Mi" => 1 << 20,
+        "Gi" => 1 << 30,
+        "Ti" => 1 << 40,
+        _ => return Err(invalid()),
+    };
+    value.checked_mul(multiplier).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("0"), Ok(0));
+        assert_eq!(parse_size("500"), Ok(500));
+        assert_eq!(parse_size("500B"), Ok(500));
+        assert_eq!(parse_size("10k"), Ok(10_000));
+        assert_eq!(parse_size("10M"), Ok(10_000_000));
+        assert_eq!(parse_size("1GB"), Ok(1_000_000_000));
+        assert_eq!(parse_size("1KiB"), Ok(1024));
+        assert_eq!(parse_size("2Mi"), Ok(2 * 1024 * 1024));
+    }
+
+    #[test]
+    fn test_parse_size_invalid() {
+        assert!(parse_size("").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("-1").is_err());
+        assert!(parse_size("1.5M").is_err());
+        assert!(parse_size("100000000T").is_err());
+    }
+}
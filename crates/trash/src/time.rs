@@ -0,0 +1,220 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Time parsing.
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+
+use crate::cli::TimeStyle;
+
+/// Formats accepted for absolute times.
+const DATETIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Parse a time given on the command line.
+///
+/// The time is either absolute (e.g. `2025-09-08T22:11:09`, `2025-09-08 22:11` or `2025-09-08`)
+/// or relative to now (e.g. `10m`, `1h30m` or `2d`).
+///
+/// This function is meant to be used as a value parser by clap.
+pub(crate) fn parse_time(s: &str) -> Result<NaiveDateTime, String> {
+    if let Some(duration) = parse_duration(s) {
+        return Ok(Local::now().naive_local() - duration);
+    }
+    for format in DATETIME_FORMATS {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(s, format) {
+            return Ok(datetime);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date.and_time(NaiveTime::MIN));
+    }
+    Err(format!(
+        "invalid time (expected e.g. '10m', '2d' or '2025-09-08T22:11:09'): {s}"
+    ))
+}
+
+/// Parse a duration such as `10m` or `1h30m`.
+///
+/// The units are `s` (seconds), `m` (minutes), `h` (hours), `d` (days) and `w` (weeks).
+fn parse_duration(s: &str) -> Option<TimeDelta> {
+    if s.is_empty() {
+        return None;
+    }
+    let mut duration = TimeDelta::zero();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return None;
+        }
+        let value = rest[..digits].parse::<i64>().ok()?;
+        let mut chars = rest[digits..].chars();
+        let unit = match chars.next()? {
+            's' => TimeDelta::try_seconds(value)?,
+            'm' => TimeDelta::try_minutes(value)?,
+            'h' => TimeDelta::try_hours(value)?,
+            'd' => TimeDelta::try_days(value)?,
+            'w' => TimeDelta::try_weeks(value)?,
+            _ => return None,
+        };
+        duration = duration.checked_add(&unit)?;
+        rest = chars.as_str();
+    }
+    Some(duration)
+}
+
+/// Format the given time in the given style.
+///
+/// The `relative` style is relative to the given current time.
+pub(crate) fn format_time(
+    datetime: &NaiveDateTime,
+    style: &TimeStyle,
+    now: &NaiveDateTime,
+) -> String {
+    match style {
+        TimeStyle::Locale => datetime.format("%c").to_string(),
+        TimeStyle::Iso => datetime.format("%Y-%m-%d %H:%M").to_string(),
+        TimeStyle::FullIso => datetime.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        TimeStyle::Relative => format_relative_time(*now - *datetime),
+        TimeStyle::Format(format) => datetime.format(format).to_string(),
+    }
+}
+
+/// Format the given elapsed time, e.g. `3 days ago`.
+fn format_relative_time(elapsed: TimeDelta) -> String {
+    /// Units, from the largest to the smallest, with their lengths in seconds
+    const UNITS: [(&str, i64); 6] = [
+        ("year", 365 * 24 * 60 * 60),
+        ("month", 30 * 24 * 60 * 60),
+        ("week", 7 * 24 * 60 * 60),
+        ("day", 24 * 60 * 60),
+        ("hour", 60 * 60),
+        ("minute", 60),
+    ];
+
+    let seconds = elapsed.num_seconds();
+    let Some((name, count)) = UNITS
+        .iter()
+        .map(|(name, length)| (name, seconds.abs() / length))
+        .find(|(_, count)| *count > 0)
+    else {
+        return String::from("just now");
+    };
+    let plural = if count > 1 { "s" } else { "" };
+    if seconds < 0 {
+        format!("in {count} {name}{plural}")
+    } else {
+        format!("{count} {name}{plural} ago")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("10m"), Some(TimeDelta::minutes(10)));
+        assert_eq!(
+            parse_duration("1h30m"),
+            Some(TimeDelta::hours(1) + TimeDelta::minutes(30))
+        );
+        assert_eq!(parse_duration("2w"), Some(TimeDelta::days(14)));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("10"), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("10x"), None);
+    }
+
+    #[test]
+    fn test_parse_time_absolute() {
+        let date = NaiveDate::from_ymd_opt(2025, 9, 8).unwrap();
+        assert_eq!(
+            parse_time("2025-09-08T22:11:09"),
+            Ok(date.and_time(NaiveTime::from_hms_opt(22, 11, 9).unwrap()))
+        );
+        assert_eq!(
+            parse_time("2025-09-08 22:11"),
+            Ok(date.and_time(NaiveTime::from_hms_opt(22, 11, 0).unwrap()))
+        );
+        assert_eq!(parse_time("2025-09-08"), Ok(date.and_time(NaiveTime::MIN)));
+        assert!(parse_time("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_relative() {
+        let before = Local::now().naive_local() - TimeDelta::minutes(10);
+        let time = parse_time("10m").unwrap();
+        let after = Local::now().naive_local() - TimeDelta::minutes(10);
+        assert!(before <= time && time <= after);
+    }
+
+    #[test]
+    fn test_format_time() {
+        let datetime = NaiveDate::from_ymd_opt(2025, 9, 8)
+            .unwrap()
+            .and_hms_opt(22, 11, 9)
+            .unwrap();
+        let now = datetime + TimeDelta::days(3) + TimeDelta::hours(2);
+        assert_eq!(
+            format_time(&datetime, &TimeStyle::Iso, &now),
+            "2025-09-08 22:11"
+        );
+        assert_eq!(
+            format_time(&datetime, &TimeStyle::FullIso, &now),
+            "2025-09-08T22:11:09"
+        );
+        assert_eq!(
+            format_time(&datetime, &TimeStyle::Relative, &now),
+            "3 days ago"
+        );
+        assert_eq!(
+            format_time(
+                &datetime,
+                &TimeStyle::Format(String::from("%d/%m/%Y")),
+                &now
+            ),
+            "08/09/2025"
+        );
+    }
+
+    #[test]
+    fn test_time_style_from_str() {
+        assert_eq!("iso".parse::<TimeStyle>(), Ok(TimeStyle::Iso));
+        assert_eq!(
+            "+%d/%m/%Y".parse::<TimeStyle>(),
+            Ok(TimeStyle::Format(String::from("%d/%m/%Y")))
+        );
+        for time_style in ["+%z", "+%:z", "+%Z", "+%Y %z", "+%+", "+%Q", "%Y"] {
+            assert!(time_style.parse::<TimeStyle>().is_err(), "{time_style}");
+        }
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        assert_eq!(format_relative_time(TimeDelta::seconds(30)), "just now");
+        assert_eq!(format_relative_time(TimeDelta::minutes(1)), "1 minute ago");
+        assert_eq!(format_relative_time(TimeDelta::minutes(90)), "1 hour ago");
+        assert_eq!(format_relative_time(TimeDelta::days(14)), "2 weeks ago");
+        assert_eq!(format_relative_time(TimeDelta::days(400)), "1 year ago");
+        assert_eq!(format_relative_time(TimeDelta::hours(-5)), "in 5 hours");
+    }
+}
@@ -33,10 +33,19 @@ fn temp_file(name: impl AsRef<OsStr>, contents: impl AsRef<str>) -> Result<Named
     Ok(temp_file)
 }
 
+/// Environment variable that overrides the base directory of the trash.
+const TRASH_DIR_ENV: &str = "IRON_BIN_TRASH_DIR";
+
+/// Return a command that works on the trash in the given data directory.
+///
+/// The trash is given by [`TRASH_DIR_ENV`], while the XDG data home points to another directory,
+/// so that any use of the home trash is detected.
 fn trash_command(data_dir: impl AsRef<Path>) -> Result<Command> {
     let data_dir = data_dir.as_ref();
     let mut command = Command::cargo_bin("trash")?;
-    command.env("XDG_DATA_HOME", data_dir);
+    command
+        .env("XDG_DATA_HOME", data_dir.join("xdg"))
+        .env(TRASH_DIR_ENV, data_dir.join("Trash"));
     Ok(command)
 }
 
@@ -118,6 +127,52 @@ fn test_put_file_and_list() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_put_file_list_and_restore_home_trash() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    // NOTE: Only the XDG data home is set, so that the home trash is used
+    let home_trash_command = || -> Result<Command> {
+        let mut command = Command::cargo_bin("trash")?;
+        command
+            .env("XDG_DATA_HOME", data_dir.path())
+            .env_remove(TRASH_DIR_ENV);
+        Ok(command)
+    };
+    home_trash_command()?
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(!file.exists(), "the trashed file is still present");
+    data_dir
+        .child("Trash/info/test.txt.trashinfo")
+        .assert(predicate::path::is_file());
+    data_dir
+        .child("Trash/files/test.txt")
+        .assert(predicate::path::is_file());
+    home_trash_command()?
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(format!("{}\n", file.path().to_str().unwrap()))
+        .stderr(predicate::str::is_empty());
+    home_trash_command()?
+        .arg("restore")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(file.exists(), "the restored file is absent");
+    data_dir
+        .child("Trash/files/test.txt")
+        .assert(predicate::path::missing());
+    Ok(())
+}
+
 #[test]
 fn test_put_files() -> Result<()> {
     let data_dir = temp_dir()?;
@@ -181,3 +236,1176 @@ fn test_put_file_and_restore_most_recent() -> Result<()> {
     assert!(file.exists(), "the restored file is absent");
     Ok(())
 }
+
+#[test]
+fn test_put_file_dry_run() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    let path = file.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg("--dry-run")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "trashed {} on ",
+            path.to_str().unwrap()
+        )))
+        .stdout(predicate::str::contains("\noperation "))
+        .stdout(predicate::str::ends_with("total 1 trashed\n"))
+        .stderr(predicate::str::is_empty());
+    assert!(file.exists(), "the file was trashed");
+    assert_eq!(
+        data_dir.read_dir()?.count(),
+        0,
+        "the trash directory was created"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_put_file_and_restore_dry_run() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("-n")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("total 1 restored\n"))
+        .stderr(predicate::str::is_empty());
+    assert!(!file.exists(), "the file was restored");
+    Ok(())
+}
+
+#[test]
+fn test_put_file_and_list_jsonl() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    let path = file.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--format=jsonl")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "{{\"identifier\":\"test.txt\",\"original_path\":\"{}\",\"deletion_time\":\"",
+            path.to_str().unwrap()
+        )))
+        .stdout(predicate::str::contains(
+            "\"size\":3,\"file_type\":\"file\",\"trash_dir\":",
+        ))
+        .stderr(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_list_csv_empty_trash() -> Result<()> {
+    trash_command(temp_dir()?.path())?
+        .arg("list")
+        .arg("--format=csv")
+        .assert()
+        .success()
+        .stdout("identifier,original_path,deletion_time,size,file_type,trash_dir,operation\n")
+        .stderr(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_put_files_from_stdin_and_list_null() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file1 = temp_file("test1.txt", "abc")?;
+    let file2 = temp_file("test\nwith newline.txt", "def")?;
+    let path1 = file1.path().canonicalize()?;
+    let path2 = file2.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg("-0")
+        .arg("--files-from=-")
+        .write_stdin(format!("{}\0{}\0", path1.display(), path2.display()))
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(!file1.exists(), "the trashed file is still present");
+    assert!(!file2.exists(), "the trashed file is still present");
+    let mut expected_paths = [path1, path2];
+    expected_paths.sort();
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--null")
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\0{}\0",
+            expected_paths[0].display(),
+            expected_paths[1].display()
+        ))
+        .stderr(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_put_and_restore_files_from_file() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file1 = temp_file("test1.txt", "abc")?;
+    let file2 = temp_file("test2.txt", "def")?;
+    let list_file = temp_file("list.txt", format!("{}\n", file1.path().display()))?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file1.path())
+        .arg(file2.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("--files-from")
+        .arg(list_file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(file1.exists(), "the restored file is absent");
+    assert!(!file2.exists(), "the file not restored is present");
+    Ok(())
+}
+
+#[test]
+fn test_put_and_restore_files_from_literal() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let files_dir = temp_dir()?;
+    let file1 = files_dir.child("test[12].txt");
+    let file2 = files_dir.child("test1.txt");
+    for file in [&file1, &file2] {
+        file.write_str("abc")?;
+    }
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file1.path())
+        .arg(file2.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("--files-from=-")
+        .write_stdin(format!(
+            "{}\n",
+            files_dir
+                .path()
+                .canonicalize()?
+                .join("test[12].txt")
+                .display()
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(file1.exists(), "the restored file is absent");
+    assert!(!file2.exists(), "the file not restored is present");
+    Ok(())
+}
+
+#[test]
+fn test_put_files_and_restore_glob() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let files_dir = temp_dir()?;
+    let file1 = files_dir.child("test1.jpg");
+    let file2 = files_dir.child("test2.jpg");
+    let file3 = files_dir.child("test3.png");
+    for file in [&file1, &file2, &file3] {
+        file.write_str("abc")?;
+    }
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file1.path())
+        .arg(file2.path())
+        .arg(file3.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg(files_dir.path().canonicalize()?.join("*.jpg"))
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(file1.exists(), "the restored file is absent");
+    assert!(file2.exists(), "the restored file is absent");
+    assert!(!file3.exists(), "the file not restored is present");
+    Ok(())
+}
+
+#[test]
+fn test_put_files_and_restore_relative_glob_special_dir() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let files_dir = temp_dir()?;
+    let file1 = files_dir.child("a[1]/test1.jpg");
+    let file2 = files_dir.child("a[1]/test2.png");
+    for file in [&file1, &file2] {
+        file.write_str("abc")?;
+    }
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file1.path())
+        .arg(file2.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .current_dir(files_dir.child("a[1]").path())
+        .arg("restore")
+        .arg("*.jpg")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(file1.exists(), "the restored file is absent");
+    assert!(!file2.exists(), "the file not restored is present");
+    Ok(())
+}
+
+#[test]
+fn test_put_files_and_restore_regex() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let files_dir = temp_dir()?;
+    let file1 = files_dir.child("test1.jpg");
+    let file2 = files_dir.child("test2.png");
+    for file in [&file1, &file2] {
+        file.write_str("abc")?;
+    }
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file1.path())
+        .arg(file2.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("--regex")
+        .arg(".*/test[0-9]\\.png")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(!file1.exists(), "the file not restored is present");
+    assert!(file2.exists(), "the restored file is absent");
+    Ok(())
+}
+
+#[test]
+fn test_put_files_and_restore_under() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let files_dir = temp_dir()?;
+    let file1 = files_dir.child("project/test1.txt");
+    let file2 = files_dir.child("project/src/test2.txt");
+    let file3 = files_dir.child("other/test3.txt");
+    for file in [&file1, &file2, &file3] {
+        file.write_str("abc")?;
+    }
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file1.path())
+        .arg(file2.path())
+        .arg(file3.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("-v")
+        .arg("--under")
+        .arg(files_dir.child("project").path())
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("total 2 restored\n"))
+        .stderr(predicate::str::is_empty());
+    assert!(file1.exists(), "the restored file is absent");
+    assert!(file2.exists(), "the restored file is absent");
+    assert!(!file3.exists(), "the file not restored is present");
+    Ok(())
+}
+
+#[test]
+fn test_put_file_and_restore_since() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("--between")
+        .arg("2000-01-01")
+        .arg("2000-01-02")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(!file.exists(), "the file not restored is present");
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("--since")
+        .arg("10m")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(file.exists(), "the restored file is absent");
+    Ok(())
+}
+
+#[test]
+fn test_put_files_and_undo() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file1 = temp_file("test1.txt", "abc")?;
+    let file2 = temp_file("test2.txt", "def")?;
+    let file3 = temp_file("test3.txt", "ghi")?;
+    let output = trash_command(data_dir.path())?
+        .arg("put")
+        .arg("-v")
+        .arg(file1.path())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let operation = String::from_utf8(output)?
+        .lines()
+        .find_map(|line| line.strip_prefix("operation "))
+        .map(ToOwned::to_owned)
+        .expect("the operation is not displayed");
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file2.path())
+        .arg(file3.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("undo")
+        .arg("-v")
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("total 2 restored\n"))
+        .stderr(predicate::str::is_empty());
+    assert!(!file1.exists(), "the file not restored is present");
+    assert!(file2.exists(), "the restored file is absent");
+    assert!(file3.exists(), "the restored file is absent");
+    trash_command(data_dir.path())?
+        .arg("undo")
+        .arg("-v")
+        .arg(&operation)
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("total 1 restored\n"))
+        .stderr(predicate::str::is_empty());
+    assert!(file1.exists(), "the restored file is absent");
+    Ok(())
+}
+
+#[test]
+fn test_undo_conflict() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file1 = temp_file("test1.txt", "abc")?;
+    let file2 = temp_file("test2.txt", "def")?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file1.path())
+        .arg(file2.path())
+        .assert()
+        .success();
+    file1.write_str("new")?;
+    trash_command(data_dir.path())?
+        .arg("undo")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"))
+        .stderr(predicate::str::contains("1 not restored"));
+    assert!(file2.exists(), "the restored file is absent");
+    Ok(())
+}
+
+#[test]
+fn test_undo_empty_trash() -> Result<()> {
+    trash_command(temp_dir()?.path())?
+        .arg("undo")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no operation to undo"));
+    Ok(())
+}
+
+#[test]
+fn test_put_file_twice_and_versions() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    for contents in ["abc", "defg"] {
+        file.write_str(contents)?;
+        trash_command(data_dir.path())?
+            .arg("put")
+            .arg(file.path())
+            .assert()
+            .success();
+    }
+    trash_command(data_dir.path())?
+        .arg("versions")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("total 2\n"))
+        .stdout(predicate::str::contains("test.txt_1"))
+        .stderr(predicate::str::is_empty());
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("--version=3")
+        .arg(file.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("version 3 of file"));
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("--version=2")
+        .assert()
+        .code(2);
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("--id=test.txt_1")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert_eq!(fs::read_to_string(file.path())?, "defg");
+    Ok(())
+}
+
+#[test]
+fn test_put_file_and_list_show_id() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    let path = file.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--show-id")
+        .assert()
+        .success()
+        .stdout(format!("test.txt {}\n", path.to_str().unwrap()))
+        .stderr(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_put_files_and_rm_id() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file1 = temp_file("test1.txt", "abc")?;
+    let file2 = temp_file("test2.txt", "def")?;
+    let path2 = file2.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file1.path())
+        .arg(file2.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("rm")
+        .arg("--id=test1.txt")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+    assert!(!file1.exists(), "the removed file is present");
+    trash_command(data_dir.path())?
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(format!("{}\n", path2.to_str().unwrap()));
+    Ok(())
+}
+
+#[test]
+fn test_put_file_and_info() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    let path = file.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("info")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("identifier: test.txt\n"))
+        .stdout(predicate::str::contains(format!(
+            "original path: {}\n",
+            path.to_str().unwrap()
+        )))
+        .stdout(predicate::str::contains("type: file\n"))
+        .stdout(predicate::str::contains("size (computed): 3\n"))
+        .stdout(predicate::str::contains("[Trash Info]\n"))
+        .stderr(predicate::str::is_empty());
+    trash_command(data_dir.path())?
+        .arg("info")
+        .arg("--id=test.txt")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("identifier: test.txt\n"));
+    Ok(())
+}
+
+#[test]
+fn test_put_dir_and_tree_cat() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let dir = temp_dir()?;
+    dir.child("a.txt").write_str("abc")?;
+    dir.child("sub/b.txt").write_str("def")?;
+    let path = dir.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(&path)
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("tree")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\n├── a.txt\n└── sub\n    └── b.txt\n",
+            path.to_str().unwrap()
+        ))
+        .stderr(predicate::str::is_empty());
+    trash_command(data_dir.path())?
+        .arg("cat")
+        .arg(path.join("sub/b.txt"))
+        .assert()
+        .success()
+        .stdout("def")
+        .stderr(predicate::str::is_empty());
+    trash_command(data_dir.path())?
+        .arg("cat")
+        .arg(path.join("sub"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is a directory"));
+    trash_command(data_dir.path())?
+        .arg("cat")
+        .arg(path.join("c.txt"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found in trash"));
+    Ok(())
+}
+
+#[test]
+fn test_put_dir_and_restore_inner_file() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let dir = temp_dir()?;
+    dir.child("src/main.rs").write_str("abc")?;
+    dir.child("src/lib.rs").write_str("def")?;
+    let path = dir.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(&path)
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("restore")
+        .arg("--verbose")
+        .arg(path.join("src/main.rs"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "restored {}",
+            path.join("src/main.rs").to_str().unwrap()
+        )))
+        .stderr(predicate::str::is_empty());
+    dir.child("src/main.rs").assert("abc");
+    dir.child("src/lib.rs").assert(predicate::path::missing());
+    trash_command(data_dir.path())?
+        .arg("tree")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(format!(
+            "{}\n└── src\n    └── lib.rs\n",
+            path.to_str().unwrap()
+        ));
+    Ok(())
+}
+
+#[test]
+fn test_put_file_and_diff() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "a\nb\nc\n")?;
+    let path = file.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success();
+    file.write_str("a\nB\nc\n")?;
+    trash_command(data_dir.path())?
+        .arg("diff")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "\n+++ {}\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n",
+            path.to_str().unwrap()
+        )))
+        .stderr(predicate::str::is_empty());
+    trash_command(data_dir.path())?
+        .arg("diff")
+        .arg("--version=2")
+        .arg(file.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("version 2"));
+    Ok(())
+}
+
+#[test]
+fn test_put_files_and_list_filter_sort() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let dir = temp_dir()?;
+    dir.child("a.txt").write_str("abcdef")?;
+    dir.child("b.rs").write_str("abc")?;
+    dir.child("sub/c.txt").write_str("a")?;
+    let path = dir.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(path.join("a.txt"))
+        .arg(path.join("b.rs"))
+        .arg(path.join("sub"))
+        .assert()
+        .success();
+    let line = |name: &str| format!("{}\n", path.join(name).to_str().unwrap());
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--type=file")
+        .arg("--sort=size")
+        .assert()
+        .success()
+        .stdout(line("a.txt") + &line("b.rs"));
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--type=file")
+        .arg("--sort=size")
+        .arg("--reverse")
+        .assert()
+        .success()
+        .stdout(line("b.rs") + &line("a.txt"));
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--name=*.txt")
+        .assert()
+        .success()
+        .stdout(line("a.txt"));
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--larger-than=3")
+        .assert()
+        .success()
+        .stdout(line("a.txt"));
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--type=dir")
+        .arg("--newer-than=1h")
+        .arg("--under")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(line("sub"));
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--older-than=1h")
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+#[test]
+fn test_put_file_and_list_columns() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    let path = file.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("-v")
+        .arg("--columns=type,id,path")
+        .arg("--no-header")
+        .assert()
+        .success()
+        .stdout(format!(
+            "total 1\n file  test.txt  {} \n",
+            path.to_str().unwrap()
+        ));
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("-v")
+        .arg("--columns=time")
+        .arg("--time-style=relative")
+        .assert()
+        .success()
+        .stdout("total 1\n deletion time \n just now      \n");
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("-v")
+        .arg("--time-style=+%Q")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid time format"));
+    Ok(())
+}
+
+#[test]
+fn test_put_files_and_list_color() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let dir = temp_dir()?;
+    dir.child("a.txt").write_str("abc")?;
+    dir.child("sub/b.txt").write_str("def")?;
+    let path = dir.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(path.join("a.txt"))
+        .arg(path.join("sub"))
+        .assert()
+        .success();
+    let path_str = |name: &str| path.join(name).to_str().unwrap().to_string();
+    trash_command(data_dir.path())?
+        .env("LS_COLORS", "di=01;34:*.txt=01;32")
+        .arg("list")
+        .arg("--color")
+        .assert()
+        .success()
+        .stdout(format!(
+            "\x1b[1;32m{}\x1b[0m\n\x1b[1;34m{}\x1b[0m\n",
+            path_str("a.txt"),
+            path_str("sub")
+        ));
+    trash_command(data_dir.path())?
+        .env("LS_COLORS", "di=01;34:*.txt=01;32")
+        .arg("list")
+        .arg("--color=never")
+        .assert()
+        .success()
+        .stdout(format!("{}\n{}\n", path_str("a.txt"), path_str("sub")));
+    Ok(())
+}
+
+#[test]
+fn test_put_files_and_stats() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let dir = temp_dir()?;
+    dir.child("a.txt").write_str("abc")?;
+    dir.child("sub/b").write_str("defgh")?;
+    let path = dir.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(path.join("a.txt"))
+        .arg(path.join("sub"))
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .env("HOME", &path)
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("entries: 2\nsize: 8\n"))
+        .stdout(predicate::str::contains("by directory:\n"))
+        .stdout(predicate::str::contains("       2     8  ~ "))
+        .stdout(predicate::str::contains("       1     5  (directory) "))
+        .stdout(predicate::str::contains("       1     3  .txt "))
+        .stdout(predicate::str::contains("       2     8  less than 1 day "))
+        .stderr(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_stats_empty_trash() -> Result<()> {
+    let data_dir = temp_dir()?;
+    trash_command(data_dir.path())?
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout("entries: 0\nsize: 0\n");
+    Ok(())
+}
+
+#[test]
+fn test_fsck_and_repair() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success();
+    let trash_dir = data_dir.child("Trash");
+    fs::remove_file(trash_dir.child("files/test.txt"))?;
+    trash_dir.child("files/orphan").write_str("def")?;
+    trash_command(data_dir.path())?
+        .arg("fsck")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("dangling trashinfo files: 1\n"))
+        .stdout(predicate::str::contains("orphan files: 1\n"))
+        .stdout(predicate::str::ends_with("total 2 problems\n"))
+        .stderr(predicate::str::contains("2 problems found"));
+    trash_command(data_dir.path())?
+        .arg("fsck")
+        .arg("--repair")
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("total 2 problems, 2 repaired\n"));
+    assert!(trash_dir.child("info/test.txt.trashinfo").exists());
+    trash_command(data_dir.path())?
+        .arg("fsck")
+        .arg("--repair")
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("total 2 problems, 2 repaired\n"));
+    trash_command(data_dir.path())?
+        .arg("fsck")
+        .assert()
+        .success()
+        .stdout("total 0 problems\n");
+    trash_command(data_dir.path())?
+        .arg("list")
+        .assert()
+        .success()
+        .stdout("/unknown-origin/orphan\n");
+    Ok(())
+}
+
+#[test]
+fn test_list_broken_entry() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file1 = temp_file("test1.txt", "abc")?;
+    let file2 = temp_file("test2.txt", "def")?;
+    let path2 = file2.path().canonicalize()?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file1.path())
+        .arg(file2.path())
+        .assert()
+        .success();
+    fs::remove_file(data_dir.child("Trash/files/test1.txt"))?;
+    trash_command(data_dir.path())?
+        .arg("list")
+        .assert()
+        .code(3)
+        .stdout(format!("{}\n", path2.to_str().unwrap()))
+        .stderr(predicate::str::starts_with(
+            "warning: broken entry test1.txt (trashinfo file ",
+        ))
+        .stderr(predicate::str::ends_with("warning: 1 broken entries\n"));
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--strict")
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicate::str::starts_with(
+            "error: broken entry test1.txt (trashinfo file ",
+        ))
+        .stderr(predicate::str::ends_with("error: 1 broken entries\n"));
+    Ok(())
+}
+
+#[test]
+fn test_put_files_and_complete_paths() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file1 = temp_file("test1.txt", "abc")?;
+    let file2 = temp_file("test2.txt", "def")?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file1.path())
+        .arg(file2.path())
+        .assert()
+        .success();
+    let path1 = file1.path().to_str().unwrap();
+    let path2 = file2.path().to_str().unwrap();
+    for command in ["restore", "rm", "info"] {
+        trash_command(data_dir.path())?
+            .env("COMPLETE", "fish")
+            .args(["--", "trash", command, &path1[..path1.len() - 5]])
+            .assert()
+            .success()
+            .stdout(format!("{path1}\n"));
+    }
+    trash_command(data_dir.path())?
+        .env("COMPLETE", "fish")
+        .args(["--", "trash", "restore", path1, ""])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("{path2}\n")));
+    Ok(())
+}
+
+fn rmtrash_command(data_dir: impl AsRef<Path>) -> Result<Command> {
+    let data_dir = data_dir.as_ref();
+    let mut command = Command::cargo_bin("rmtrash")?;
+    command
+        .env("XDG_DATA_HOME", data_dir.join("xdg"))
+        .env(TRASH_DIR_ENV, data_dir.join("Trash"));
+    Ok(command)
+}
+
+#[test]
+fn test_rmtrash_files() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let dir = temp_dir()?;
+    dir.child("a.txt").write_str("abc")?;
+    dir.child("sub/b.txt").write_str("def")?;
+    dir.child("empty").create_dir_all()?;
+    rmtrash_command(data_dir.path())?
+        .current_dir(dir.path())
+        .args(["-v", "a.txt", "sub", "missing.txt", "."])
+        .assert()
+        .code(1)
+        .stdout("removed 'a.txt'\n")
+        .stderr(
+            "rmtrash: cannot remove 'sub': Is a directory\n\
+             rmtrash: cannot remove 'missing.txt': No such file or directory\n\
+             rmtrash: refusing to remove '.' or '..' directory: skipping '.'\n",
+        );
+    rmtrash_command(data_dir.path())?
+        .current_dir(dir.path())
+        .args(["-d", "sub", "empty"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::is_empty())
+        .stderr("rmtrash: cannot remove 'sub': Directory not empty\n");
+    rmtrash_command(data_dir.path())?
+        .current_dir(dir.path())
+        .args(["-rfv", "sub", "missing.txt"])
+        .assert()
+        .success()
+        .stdout("removed directory 'sub'\n")
+        .stderr(predicate::str::is_empty());
+    assert_eq!(dir.read_dir()?.count(), 0);
+    assert_eq!(data_dir.child("Trash/info").read_dir()?.count(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_rmtrash_usage() -> Result<()> {
+    let data_dir = temp_dir()?;
+    rmtrash_command(data_dir.path())?
+        .assert()
+        .code(1)
+        .stderr("rmtrash: missing operand\nTry 'rmtrash --help' for more information.\n");
+    rmtrash_command(data_dir.path())?
+        .arg("-f")
+        .assert()
+        .success();
+    rmtrash_command(data_dir.path())?
+        .arg("--unknown")
+        .assert()
+        .code(1);
+    rmtrash_command(data_dir.path())?
+        .args(["-r", "/"])
+        .assert()
+        .code(1)
+        .stderr(
+            "rmtrash: it is dangerous to operate recursively on '/'\n\
+             rmtrash: use --no-preserve-root to override this failsafe\n",
+        );
+    Ok(())
+}
+
+#[test]
+fn test_rmtrash_usage_name() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let bin_dir = temp_dir()?;
+    let link_path = bin_dir.child("rm");
+    std::os::unix::fs::symlink(assert_cmd::cargo::cargo_bin("rmtrash"), link_path.path())?;
+    let mut command = Command::new(link_path.path());
+    command
+        .env("XDG_DATA_HOME", data_dir.path().join("xdg"))
+        .env(TRASH_DIR_ENV, data_dir.path().join("Trash"))
+        .arg("--unknown")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Usage: rm "));
+    Ok(())
+}
+
+#[test]
+fn test_rmtrash_interactive() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let dir = temp_dir()?;
+    for name in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+        dir.child(name).write_str(name)?;
+    }
+    rmtrash_command(data_dir.path())?
+        .current_dir(dir.path())
+        .args(["-i", "a.txt", "b.txt"])
+        .write_stdin("y\nn\n")
+        .assert()
+        .success()
+        .stderr(
+            "rmtrash: remove regular file 'a.txt'? [y/N] \
+             rmtrash: remove regular file 'b.txt'? [y/N] ",
+        );
+    rmtrash_command(data_dir.path())?
+        .current_dir(dir.path())
+        .args(["-I", "b.txt", "c.txt", "d.txt", "missing.txt"])
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stderr("rmtrash: remove 4 arguments? [y/N] ");
+    assert_eq!(dir.read_dir()?.count(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_put_files_interactive_once_not_terminal() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let dir = temp_dir()?;
+    for name in ["a.txt", "b.txt", "c.txt", "sub/d.txt"] {
+        dir.child(name).write_str(name)?;
+    }
+    // NOTE: The output is not a terminal, so there is no prompt
+    trash_command(data_dir.path())?
+        .current_dir(dir.path())
+        .args(["put", "-i", "-I", "a.txt", "b.txt", "c.txt", "sub"])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+    assert_eq!(dir.read_dir()?.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_restore_pick_not_terminal() -> Result<()> {
+    let data_dir = temp_dir()?;
+    trash_command(data_dir.path())?
+        .args(["restore", "--pick"])
+        .assert()
+        .failure()
+        .stderr("error: cannot pick files: the output is not a terminal\n");
+    trash_command(data_dir.path())?
+        .args(["restore", "--sort", "size"])
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+#[test]
+fn test_option_trash_dir() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let other_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    trash_command(data_dir.path())?
+        .arg("--trash-dir")
+        .arg(other_dir.path())
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success();
+    assert!(other_dir.child("info/test.txt.trashinfo").exists());
+    assert!(!data_dir.child("Trash").exists());
+    let path = file.path().to_str().unwrap();
+    trash_command(data_dir.path())?
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    // NOTE: The option is global, so it can also follow the subcommand
+    trash_command(data_dir.path())?
+        .arg("list")
+        .arg("--trash-dir")
+        .arg(other_dir.path())
+        .assert()
+        .success()
+        .stdout(format!("{path}\n"));
+    trash_command(data_dir.path())?
+        .env(TRASH_DIR_ENV, other_dir.path())
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(format!("{path}\n"));
+    Ok(())
+}
+
+#[test]
+fn test_restore_from_mounted_trash() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let top_dir = temp_dir()?;
+    let trash_dir = top_dir.child(".Trash-1000");
+    trash_dir
+        .child("info/a.txt.trashinfo")
+        .write_str("[Trash Info]\nPath=Documents/a.txt\nDeletionDate=2025-09-08T22:11:09\n")?;
+    trash_dir.child("files/a.txt").write_str("abc")?;
+    top_dir.child("Documents").create_dir_all()?;
+    trash_command(data_dir.path())?
+        .arg("--trash-dir")
+        .arg(trash_dir.path())
+        .arg("restore")
+        .arg(top_dir.child("Documents/a.txt").path())
+        .assert()
+        .success();
+    top_dir.child("Documents/a.txt").assert("abc");
+    Ok(())
+}
+
+#[test]
+fn test_rm_without_selector() -> Result<()> {
+    let data_dir = temp_dir()?;
+    let file = temp_file("test.txt", "abc")?;
+    trash_command(data_dir.path())?
+        .arg("put")
+        .arg(file.path())
+        .assert()
+        .success();
+    trash_command(data_dir.path())?
+        .arg("rm")
+        .write_stdin("")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "the following required arguments were not provided",
+        ));
+    data_dir
+        .child("Trash/info/test.txt.trashinfo")
+        .assert(predicate::path::exists());
+    Ok(())
+}
+
+#[test]
+fn test_list_time_style_time_zone() -> Result<()> {
+    let data_dir = temp_dir()?;
+    trash_command(data_dir.path())?
+        .args(["list", "-v", "--time-style=+%z"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("invalid time format: %z"));
+    Ok(())
+}
@@ -0,0 +1,41 @@
+// Copyright 2025 Laurent Pireyn
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fs::File, path::PathBuf};
+
+use clap::CommandFactory;
+use clap_complete::env::{Bash, EnvCompleter, Fish, Zsh};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Shells, with the names of their completion scripts.
+const SHELLS: [(&dyn EnvCompleter, &str); 3] = [
+    (&Bash, "trash.bash"),
+    (&Fish, "trash.fish"),
+    (&Zsh, "_trash"),
+];
+
+fn main() -> Result<()> {
+    let command = trash::cli::Cli::command();
+    let name = command.get_name();
+    let out_dir = PathBuf::from("target/completions");
+    std::fs::create_dir_all(&out_dir)?;
+    // NOTE: The scripts only register the `trash` command, which prints the completions itself,
+    // so that the original paths in the trash can be completed
+    for (shell, file_name) in SHELLS {
+        let mut file = File::create(out_dir.join(file_name))?;
+        shell.write_registration("COMPLETE", name, name, name, &mut file)?;
+    }
+    Ok(())
+}